@@ -1,14 +1,17 @@
 #![allow(dead_code)]
 use anyhow::{anyhow, Result};
 use futures::Future;
-use wasm_bindgen::JsCast;
+use wasm_bindgen::{convert::FromWasmAbi, prelude::Closure, JsCast};
+use wasm_bindgen_futures::JsFuture;
 
 use web_sys::{
-    Document, HtmlButtonElement, HtmlCanvasElement, HtmlImageElement, WebGlRenderingContext, Window,
+    Document, Element, EventTarget, HtmlButtonElement, HtmlCanvasElement, HtmlElement,
+    HtmlImageElement, HtmlInputElement, HtmlSelectElement, Node, Response, UrlSearchParams,
+    WebGlRenderingContext, Window,
 };
 macro_rules! log {
     ($($t:tt)*) => {
-        web_sys::console::log_1(format!($($t)*).into());
+        web_sys::console::log_1(&format!($($t)*).into())
     }
 }
 
@@ -53,6 +56,19 @@ pub fn button(button_id: impl AsRef<str>) -> Result<HtmlButtonElement> {
             ))
         })
 }
+pub fn select(select_id: &str) -> Result<HtmlSelectElement> {
+    document()?
+        .get_element_by_id(select_id)
+        .ok_or_else(|| anyhow!("Failed to get select with id {:?}", select_id))?
+        .dyn_into::<HtmlSelectElement>()
+        .map_err(|element| {
+            anyhow!(format!(
+                "Failed to convert {:#?} into HtmlSelectElement",
+                element
+            ))
+        })
+}
+
 pub fn context() -> Result<WebGlRenderingContext> {
     canvas()?
         .get_context("webgl")
@@ -98,9 +114,148 @@ pub fn width() -> Result<u32> {
         .ok_or_else(|| anyhow!("Failed to convert height to number"))?;
     Ok(height as u32)
 }
+/// Body of response to GET request for given url, failed status is an error.
+pub async fn fetch_text(url: &str) -> Result<String> {
+    let response = JsFuture::from(window()?.fetch_with_str(url))
+        .await
+        .map_err(|err| anyhow!(format!("Failed to fetch {:?} with error {:#?}", url, err)))?
+        .dyn_into::<Response>()
+        .map_err(|err| anyhow!(format!("Failed to get response {:#?}", err)))?;
+    if !response.ok() {
+        return Err(anyhow!(
+            "Failed to fetch {:?} with status {}",
+            url,
+            response.status()
+        ));
+    }
+    let text = response
+        .text()
+        .map_err(|err| anyhow!(format!("Failed to read {:?} with error {:#?}", url, err)))?;
+    JsFuture::from(text)
+        .await
+        .map_err(|err| anyhow!(format!("Failed to read {:?} with error {:#?}", url, err)))?
+        .as_string()
+        .ok_or_else(|| anyhow!("Response of {:?} is not text", url))
+}
+
+/// Milliseconds since page was loaded, not affected by changes to system clock.
+pub fn now() -> Result<f64> {
+    window()?
+        .performance()
+        .map(|performance| performance.now())
+        .ok_or_else(|| anyhow!("Failed to get performance"))
+}
+
+/// Resolves after given milliseconds, without blocking page.
+pub async fn sleep(millis: i32) -> Result<()> {
+    let window = window()?;
+    let mut scheduled = Ok(0);
+    let timeout = js_sys::Promise::new(&mut |resolve, _| {
+        scheduled = window.set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, millis);
+    });
+    scheduled.map_err(|err| anyhow!(format!("Failed to set timeout {:#?}", err)))?;
+    JsFuture::from(timeout)
+        .await
+        .map_err(|err| anyhow!(format!("Failed to wait {:#?}", err)))?;
+    Ok(())
+}
+
+/// Device pixels per css pixel, more than 1 on most phones.
+pub fn device_pixel_ratio() -> Result<f64> {
+    Ok(window()?.device_pixel_ratio())
+}
+
 pub fn spawn_local<F>(future: F)
 where
     F: Future<Output = ()> + 'static,
 {
     wasm_bindgen_futures::spawn_local(future)
 }
+
+/// Registers callback for given event, callback lives as long as page.
+pub fn add_event_listener<E>(
+    target: &EventTarget,
+    event_name: &str,
+    callback: impl FnMut(E) + 'static,
+) -> Result<()>
+where
+    E: FromWasmAbi + 'static,
+{
+    let callback = Closure::<dyn FnMut(E)>::new(callback);
+    target
+        .add_event_listener_with_callback(event_name, callback.as_ref().unchecked_ref())
+        .map_err(|err| {
+            anyhow!(format!(
+                "Failed to add listener for {:?} with error {:#?}",
+                event_name, err
+            ))
+        })?;
+    callback.forget();
+    Ok(())
+}
+
+/// Value of given parameter in query string of page url, like `shadows` in `?shadows=off`.
+pub fn query_param(name: &str) -> Result<Option<String>> {
+    let search = window()?
+        .location()
+        .search()
+        .map_err(|err| anyhow!(format!("Failed to get query string {:#?}", err)))?;
+    let params = UrlSearchParams::new_with_str(&search)
+        .map_err(|err| anyhow!(format!("Failed to parse query string {:#?}", err)))?;
+    Ok(params.get(name))
+}
+
+pub fn body() -> Result<HtmlElement> {
+    document()?
+        .body()
+        .ok_or_else(|| anyhow!("Failed to get body"))
+}
+
+/// New element of given tag, not yet attached to page.
+pub fn create_element<T: JsCast>(tag: &str) -> Result<T> {
+    document()?
+        .create_element(tag)
+        .map_err(|err| anyhow!(format!("Failed to create {:?} with error {:#?}", tag, err)))?
+        .dyn_into::<T>()
+        .map_err(|element| anyhow!(format!("Failed to convert {:#?}", element)))
+}
+
+/// New element with given text, appended at end of parent.
+pub fn append_element<T: JsCast + AsRef<Node>>(parent: &Node, tag: &str, text: &str) -> Result<T> {
+    let element = create_element::<T>(tag)?;
+    let node: &Node = element.as_ref();
+    node.set_text_content(Some(text));
+    parent
+        .append_child(node)
+        .map_err(|err| anyhow!(format!("Failed to append {:?} with error {:#?}", tag, err)))?;
+    Ok(element)
+}
+
+pub fn append_button(parent: &Node, text: &str) -> Result<HtmlButtonElement> {
+    append_element(parent, "button", text)
+}
+
+pub fn append_input(parent: &Node, placeholder: &str) -> Result<HtmlInputElement> {
+    let input = append_element::<HtmlInputElement>(parent, "input", "")?;
+    input.set_placeholder(placeholder);
+    Ok(input)
+}
+
+/// Drop down list, first option is picked to begin with.
+pub fn append_select(parent: &Node, options: &[impl AsRef<str>]) -> Result<HtmlSelectElement> {
+    let select = append_element::<HtmlSelectElement>(parent, "select", "")?;
+    for option in options {
+        append_element::<Element>(&select, "option", option.as_ref())?;
+    }
+    Ok(select)
+}
+
+/// Removes every child of element.
+pub fn clear(element: &Element) {
+    element.set_inner_html("");
+}
+
+/// Calls back on every click, for as long as page lives.
+pub fn on_click(target: &EventTarget, mut callback: impl FnMut() + 'static) -> Result<()> {
+    add_event_listener(target, "click", move |_: web_sys::MouseEvent| callback())
+}
@@ -0,0 +1,125 @@
+use std::{cell::RefCell, rc::Rc};
+
+use anyhow::Result;
+use web_sys::{HtmlCanvasElement, MouseEvent, TouchEvent, TouchList, WheelEvent};
+
+use crate::browser::{add_event_listener, window};
+use crate::programs::ludo::camera::Camera;
+
+const WHEEL_ZOOM_SPEED: f32 = 0.001;
+
+#[derive(Debug, Default)]
+struct Gesture {
+    // last pointer position while dragging.
+    drag: Option<(f32, f32)>,
+    // last distance b/w two fingers while pinching.
+    pinch: Option<f32>,
+}
+
+/// Drag (mouse or one finger) orbits the camera, wheel and two finger pinch zooms it.
+/// on_change is called whenever camera has moved and board needs to be drawn again.
+pub fn attach_camera_controls(
+    canvas: &HtmlCanvasElement,
+    camera: Rc<RefCell<Camera>>,
+    on_change: Rc<dyn Fn()>,
+) -> Result<()> {
+    let gesture = Rc::new(RefCell::new(Gesture::default()));
+    let window = window()?;
+
+    {
+        let gesture = gesture.clone();
+        add_event_listener(canvas, "mousedown", move |event: MouseEvent| {
+            gesture.borrow_mut().drag = Some((event.client_x() as f32, event.client_y() as f32));
+        })?;
+    }
+
+    {
+        let gesture = gesture.clone();
+        let camera = camera.clone();
+        let on_change = on_change.clone();
+        add_event_listener(&window, "mousemove", move |event: MouseEvent| {
+            let mut gesture = gesture.borrow_mut();
+            if let Some(last) = gesture.drag {
+                let position = (event.client_x() as f32, event.client_y() as f32);
+                gesture.drag = Some(position);
+                camera
+                    .borrow_mut()
+                    .orbit(position.0 - last.0, position.1 - last.1);
+                on_change();
+            }
+        })?;
+    }
+
+    {
+        let gesture = gesture.clone();
+        add_event_listener(&window, "mouseup", move |_: MouseEvent| {
+            gesture.borrow_mut().drag = None;
+        })?;
+    }
+
+    {
+        let camera = camera.clone();
+        let on_change = on_change.clone();
+        add_event_listener(canvas, "wheel", move |event: WheelEvent| {
+            event.prevent_default();
+            camera
+                .borrow_mut()
+                .zoom((event.delta_y() as f32 * WHEEL_ZOOM_SPEED).exp());
+            on_change();
+        })?;
+    }
+
+    {
+        let gesture = gesture.clone();
+        add_event_listener(canvas, "touchstart", move |event: TouchEvent| {
+            *gesture.borrow_mut() = gesture_for(&event.touches());
+        })?;
+    }
+
+    {
+        let gesture = gesture.clone();
+        add_event_listener(canvas, "touchmove", move |event: TouchEvent| {
+            event.prevent_default();
+            let current = gesture_for(&event.touches());
+            let last = gesture.replace(current);
+            let current = gesture.borrow();
+            if let (Some(last), Some(position)) = (last.drag, current.drag) {
+                camera
+                    .borrow_mut()
+                    .orbit(position.0 - last.0, position.1 - last.1);
+            } else if let (Some(last), Some(distance)) = (last.pinch, current.pinch) {
+                camera.borrow_mut().zoom(last / distance);
+            } else {
+                return;
+            }
+            on_change();
+        })?;
+    }
+
+    for event_name in ["touchend", "touchcancel"] {
+        let gesture = gesture.clone();
+        add_event_listener(canvas, event_name, move |event: TouchEvent| {
+            *gesture.borrow_mut() = gesture_for(&event.touches());
+        })?;
+    }
+
+    Ok(())
+}
+
+fn gesture_for(touches: &TouchList) -> Gesture {
+    match (touches.item(0), touches.item(1)) {
+        (Some(first), None) => Gesture {
+            drag: Some((first.client_x() as f32, first.client_y() as f32)),
+            pinch: None,
+        },
+        (Some(first), Some(second)) => {
+            let dx = (first.client_x() - second.client_x()) as f32;
+            let dy = (first.client_y() - second.client_y()) as f32;
+            Gesture {
+                drag: None,
+                pinch: Some((dx * dx + dy * dy).sqrt().max(1.)),
+            }
+        }
+        _ => Gesture::default(),
+    }
+}
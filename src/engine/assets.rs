@@ -0,0 +1,123 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use anyhow::{anyhow, Result};
+use futures::stream::{FuturesUnordered, StreamExt};
+use serde::Deserialize;
+use web_sys::{WebGlRenderingContext, WebGlTexture};
+
+use super::load_image;
+use crate::programs::texture_program::TextureProgram;
+
+/// Images a game needs, read from a json file like `{"textures": [{"name": "dice", "src": "dice.png"}]}`.
+/// It may also list theme files players can pick from, like `{"themes": ["themes/night.json"]}`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct Manifest {
+    #[serde(default)]
+    pub textures: Vec<TextureAsset>,
+    #[serde(default)]
+    pub themes: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TextureAsset {
+    pub name: String,
+    pub src: String,
+}
+
+/// How far loading has got, for a loading screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub loaded: usize,
+    pub total: usize,
+}
+
+impl Manifest {
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).map_err(|err| anyhow!("Failed to parse manifest {}", err))
+    }
+}
+
+/// Textures on GPU by name. Each texture is uploaded once, however many times it is asked for.
+#[derive(Default)]
+pub struct Assets {
+    textures: RefCell<HashMap<String, WebGlTexture>>,
+}
+
+impl Assets {
+    /// Downloads all images of manifest at once, and uploads each one as soon as it arrives.
+    /// Every image which failed is named in returned error, rest of them stay loaded.
+    pub async fn load(
+        &self,
+        gl: &WebGlRenderingContext,
+        texture_program: &TextureProgram,
+        manifest: &Manifest,
+        mut on_progress: impl FnMut(Progress),
+    ) -> Result<()> {
+        let pending: Vec<&TextureAsset> = manifest
+            .textures
+            .iter()
+            .filter(|asset| !self.textures.borrow().contains_key(&asset.name))
+            .collect();
+        let mut progress = Progress {
+            loaded: manifest.textures.len() - pending.len(),
+            total: manifest.textures.len(),
+        };
+        on_progress(progress);
+
+        let mut loading: FuturesUnordered<_> = pending
+            .into_iter()
+            .map(|asset| async move { (asset, load_image(&asset.src).await) })
+            .collect();
+        let mut failures = Vec::new();
+        while let Some((asset, image)) = loading.next().await {
+            match image.and_then(|image| texture_program.init_texture(gl, &image)) {
+                Ok(texture) => {
+                    self.textures
+                        .borrow_mut()
+                        .insert(asset.name.clone(), texture);
+                    progress.loaded += 1;
+                    on_progress(progress);
+                }
+                Err(err) => failures.push(format!("{}: {}", asset.name, err)),
+            }
+        }
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow!("Failed to load {}", failures.join(", ")))
+        }
+    }
+
+    pub fn texture(&self, name: &str) -> Option<WebGlTexture> {
+        self.textures.borrow().get(name).cloned()
+    }
+
+    /// Number of textures on GPU, it only grows while loading.
+    pub fn loaded(&self) -> usize {
+        self.textures.borrow().len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_manifest_from_json() {
+        let manifest = Manifest::from_json(
+            r#"{"textures": [{"name": "dice", "src": "dice.png"}], "themes": ["themes/night.json"]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            manifest.textures,
+            vec![TextureAsset {
+                name: "dice".to_owned(),
+                src: "dice.png".to_owned(),
+            }]
+        );
+        assert_eq!(manifest.themes, vec!["themes/night.json".to_owned()]);
+        assert_eq!(Manifest::from_json("{}").unwrap(), Manifest::default());
+        assert!(Manifest::from_json(r#"{"textures": 1}"#).is_err());
+    }
+}
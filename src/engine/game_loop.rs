@@ -0,0 +1,59 @@
+/// Length of one simulation step, in milliseconds.
+pub const STEP: f64 = 1000. / 60.;
+// longest gap b/w frames that is caught up on, rest of a longer gap (like a hidden tab) is dropped.
+const MAX_FRAME: f64 = 250.;
+
+/// Runs simulation in fixed steps, however often frames are drawn,
+/// so that animations take same time on every display.
+#[derive(Debug, Default)]
+pub struct GameLoop {
+    last: Option<f64>,
+    // time not yet simulated, always less than a step after advance.
+    accumulator: f64,
+}
+
+impl GameLoop {
+    /// Steps to simulate for frame drawn at now (in milliseconds),
+    /// along with how far (0 to 1) the frame is into next step, to interpolate what is drawn.
+    pub fn advance(&mut self, now: f64) -> (u32, f32) {
+        let elapsed = match self.last.replace(now) {
+            Some(last) => (now - last).clamp(0., MAX_FRAME),
+            None => 0.,
+        };
+        self.accumulator += elapsed;
+        let steps = (self.accumulator / STEP).floor();
+        self.accumulator -= steps * STEP;
+        (steps as u32, (self.accumulator / STEP) as f32)
+    }
+
+    /// Forgets last frame, so that time spent without drawing is not simulated once frames resume.
+    pub fn pause(&mut self) {
+        self.last = None;
+        self.accumulator = 0.;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_steps_do_not_depend_on_frame_rate() {
+        let mut slow = GameLoop::default();
+        let mut fast = GameLoop::default();
+        let (mut slow_steps, mut fast_steps) = (0, 0);
+        for frame in 0..=30 {
+            slow_steps += slow.advance(frame as f64 * 1000. / 30.).0;
+        }
+        for frame in 0..=144 {
+            fast_steps += fast.advance(frame as f64 * 1000. / 144.).0;
+        }
+        assert!((slow_steps as i32 - 60).abs() <= 1);
+        assert!((fast_steps as i32 - 60).abs() <= 1);
+
+        // a long pause only catches up on a few frames.
+        let (steps, alpha) = slow.advance(10_000.);
+        assert!(steps <= (MAX_FRAME / STEP).ceil() as u32);
+        assert!((0. ..=1.).contains(&alpha));
+    }
+}
@@ -2,7 +2,10 @@ use anyhow::{anyhow, Result};
 use js_sys::{Float32Array, Uint16Array, WebAssembly};
 use std::convert::TryFrom;
 use wasm_bindgen::{JsCast, JsValue};
-use web_sys::{WebGlProgram, WebGlRenderingContext, WebGlShader, WebGlUniformLocation};
+use web_sys::{
+    WebGlBuffer, WebGlProgram, WebGlRenderingContext, WebGlShader, WebGlTexture,
+    WebGlUniformLocation,
+};
 pub fn link_program(
     gl: &WebGlRenderingContext,
     vetrex_src: &str,
@@ -97,12 +100,8 @@ fn compile_shader(gl: &WebGlRenderingContext, src: &str, shader_type: u32) -> Re
     }
 }
 
-pub fn init_vertex(
-    gl: &WebGlRenderingContext,
-    program: &WebGlProgram,
-    attribute_name: impl AsRef<str>,
-    data: &[f32],
-) -> Result<()> {
+/// Uploads data once into a new array buffer, which can be bound again and again while drawing.
+pub fn create_buffer(gl: &WebGlRenderingContext, data: &[f32]) -> Result<WebGlBuffer> {
     let buffer = gl
         .create_buffer()
         .ok_or_else(|| anyhow!("Failed to create buffer"))?;
@@ -113,9 +112,41 @@ pub fn init_vertex(
         &data,
         WebGlRenderingContext::STATIC_DRAW,
     );
-    let attribute = attribute_location(program, &gl, attribute_name.as_ref())?;
-    gl.vertex_attrib_pointer_with_i32(attribute, 3, WebGlRenderingContext::FLOAT, false, 0, 0);
-    gl.enable_vertex_attrib_array(attribute);
-    // gl.bind_buffer(WebGlRenderingContext::ARRAY_BUFFER, None);
-    Ok(())
+    Ok(buffer)
+}
+
+pub fn create_index_buffer(gl: &WebGlRenderingContext, data: &[u16]) -> Result<WebGlBuffer> {
+    let buffer = gl
+        .create_buffer()
+        .ok_or_else(|| anyhow!("Failed to create buffer"))?;
+    gl.bind_buffer(WebGlRenderingContext::ELEMENT_ARRAY_BUFFER, Some(&buffer));
+    let data = create_int_js_memory(data)?;
+    gl.buffer_data_with_array_buffer_view(
+        WebGlRenderingContext::ELEMENT_ARRAY_BUFFER,
+        &data,
+        WebGlRenderingContext::STATIC_DRAW,
+    );
+    Ok(buffer)
+}
+
+/// One pixel texture, for drawing textured materials before their image has loaded.
+pub fn create_pixel_texture(gl: &WebGlRenderingContext, rgba: [u8; 4]) -> Result<WebGlTexture> {
+    let texture = gl
+        .create_texture()
+        .ok_or_else(|| anyhow!("Failed to create texture"))?;
+    gl.active_texture(WebGlRenderingContext::TEXTURE0);
+    gl.bind_texture(WebGlRenderingContext::TEXTURE_2D, Some(&texture));
+    gl.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+        WebGlRenderingContext::TEXTURE_2D,
+        0,
+        WebGlRenderingContext::RGBA as i32,
+        1,
+        1,
+        0,
+        WebGlRenderingContext::RGBA,
+        WebGlRenderingContext::UNSIGNED_BYTE,
+        Some(&rgba),
+    )
+    .map_err(|err| anyhow!(format!("Failed to fill texture with error {:#?}", err)))?;
+    Ok(texture)
 }
@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use nalgebra::Matrix4;
+use web_sys::{WebGlProgram, WebGlRenderingContext as GL, WebGlUniformLocation};
+
+use super::helper::{attribute_location, link_program, uniform_location};
+
+// attribute names shaders use for each part of a mesh.
+pub const POSITION: &str = "a_Position";
+pub const NORMAL: &str = "a_Normal";
+pub const COLOR: &str = "a_Color";
+pub const UV: &str = "a_TexCoord";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Uniform {
+    Int(i32),
+    Float(f32),
+    Vec3([f32; 3]),
+    Vec4([f32; 4]),
+    Mat4(Matrix4<f32>),
+}
+
+/// Locations of mesh attributes, None when shader does not use that attribute.
+#[derive(Debug, Clone, Copy)]
+pub struct Attributes {
+    pub position: Option<u32>,
+    pub normal: Option<u32>,
+    pub color: Option<u32>,
+    pub uv: Option<u32>,
+}
+
+/// Linked shader program along with its attribute and uniform locations,
+/// which are looked up once when program is linked.
+pub struct Material {
+    program: WebGlProgram,
+    attributes: Attributes,
+    uniforms: HashMap<String, WebGlUniformLocation>,
+}
+
+impl Material {
+    pub fn new(gl: &GL, vertex_src: &str, fragment_src: &str) -> Result<Self> {
+        let program = link_program(gl, vertex_src, fragment_src)?;
+        let attributes = Attributes {
+            position: attribute_location(&program, gl, POSITION).ok(),
+            normal: attribute_location(&program, gl, NORMAL).ok(),
+            color: attribute_location(&program, gl, COLOR).ok(),
+            uv: attribute_location(&program, gl, UV).ok(),
+        };
+        if attributes.position.is_none() {
+            return Err(anyhow!(format!("Shader does not have {}", POSITION)));
+        }
+
+        let count = gl
+            .get_program_parameter(&program, GL::ACTIVE_UNIFORMS)
+            .as_f64()
+            .unwrap_or(0.) as u32;
+        let mut uniforms = HashMap::new();
+        for index in 0..count {
+            let name = match gl.get_active_uniform(&program, index) {
+                Some(info) => info.name(),
+                None => continue,
+            };
+            let location = uniform_location(gl, &program, &name)?;
+            uniforms.insert(name, location);
+        }
+
+        Ok(Material {
+            program,
+            attributes,
+            uniforms,
+        })
+    }
+
+    pub fn use_program(&self, gl: &GL) {
+        gl.use_program(Some(&self.program));
+    }
+
+    pub fn attributes(&self) -> &Attributes {
+        &self.attributes
+    }
+
+    pub fn has_uniform(&self, name: &str) -> bool {
+        self.uniforms.contains_key(name)
+    }
+
+    /// Sets uniform on program, which has to be in use. Uniforms shader does not have are skipped,
+    /// so that same scene can be drawn by materials needing different inputs.
+    pub fn set(&self, gl: &GL, name: &str, value: &Uniform) {
+        let location = match self.uniforms.get(name) {
+            Some(location) => Some(location),
+            None => return,
+        };
+        match value {
+            Uniform::Int(value) => gl.uniform1i(location, *value),
+            Uniform::Float(value) => gl.uniform1f(location, *value),
+            Uniform::Vec3(value) => gl.uniform3fv_with_f32_array(location, value),
+            Uniform::Vec4(value) => gl.uniform4fv_with_f32_array(location, value),
+            Uniform::Mat4(value) => {
+                gl.uniform_matrix4fv_with_f32_array(location, false, value.as_slice())
+            }
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use anyhow::{anyhow, Result};
+use web_sys::{WebGlBuffer, WebGlRenderingContext as GL};
+
+use super::helper::{create_buffer, create_index_buffer};
+use super::material::Material;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Primitive {
+    #[default]
+    Triangles,
+    TriangleStrip,
+}
+
+impl Primitive {
+    fn mode(&self) -> u32 {
+        match self {
+            Primitive::Triangles => GL::TRIANGLES,
+            Primitive::TriangleStrip => GL::TRIANGLE_STRIP,
+        }
+    }
+}
+
+/// Geometry on cpu side. Vertices, normals and colors have three floats per vertex and uv has two.
+/// Anything other than vertices can be left empty, and without indices vertices are drawn in order.
+#[derive(Debug, Clone, Default)]
+pub struct Mesh {
+    pub vertices: Vec<f32>,
+    pub normals: Vec<f32>,
+    pub colors: Vec<f32>,
+    pub uv: Vec<f32>,
+    pub indices: Vec<u16>,
+    pub primitive: Primitive,
+}
+
+impl Mesh {
+    pub fn vertex_count(&self) -> usize {
+        self.vertices.len() / 3
+    }
+
+    /// Every attribute present must have an entry for every vertex, and indices must stay in range.
+    pub fn validate(&self) -> Result<()> {
+        let vertex_count = self.vertex_count();
+        if self.vertices.len() != vertex_count * 3 {
+            return Err(anyhow!("Vertices are not made of x, y and z"));
+        }
+        for (name, data, size) in [
+            ("normals", &self.normals, 3),
+            ("colors", &self.colors, 3),
+            ("uv", &self.uv, 2),
+        ] {
+            if !data.is_empty() && data.len() != vertex_count * size {
+                return Err(anyhow!(format!(
+                    "Mesh has {} vertices but {} {}",
+                    vertex_count,
+                    data.len() / size,
+                    name
+                )));
+            }
+        }
+        if let Some(index) = self
+            .indices
+            .iter()
+            .find(|index| **index as usize >= vertex_count)
+        {
+            return Err(anyhow!(format!(
+                "Index {} is out of {} vertices",
+                index, vertex_count
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Mesh uploaded to gpu. It is uploaded once and only bound while drawing.
+/// Its buffers are deleted once it is dropped.
+pub struct GpuMesh {
+    gl: GL,
+    vertices: WebGlBuffer,
+    normals: Option<WebGlBuffer>,
+    colors: Option<WebGlBuffer>,
+    uv: Option<WebGlBuffer>,
+    indices: Option<WebGlBuffer>,
+    count: i32,
+    primitive: Primitive,
+}
+
+impl GpuMesh {
+    pub fn new(gl: &GL, mesh: &Mesh) -> Result<Self> {
+        mesh.validate()?;
+        let optional_buffer = |data: &[f32]| {
+            if data.is_empty() {
+                Ok(None)
+            } else {
+                create_buffer(gl, data).map(Some)
+            }
+        };
+        let (indices, count) = if mesh.indices.is_empty() {
+            (None, mesh.vertex_count() as i32)
+        } else {
+            (
+                Some(create_index_buffer(gl, &mesh.indices)?),
+                mesh.indices.len() as i32,
+            )
+        };
+        Ok(GpuMesh {
+            gl: gl.clone(),
+            vertices: create_buffer(gl, &mesh.vertices)?,
+            normals: optional_buffer(&mesh.normals)?,
+            colors: optional_buffer(&mesh.colors)?,
+            uv: optional_buffer(&mesh.uv)?,
+            indices,
+            count,
+            primitive: mesh.primitive,
+        })
+    }
+
+    /// Points attributes used by material to buffers of this mesh.
+    /// Attribute missing from mesh falls back to its constant value in shader.
+    pub fn bind(&self, gl: &GL, material: &Material) {
+        let attributes = material.attributes();
+        bind_attribute(gl, attributes.position, Some(&self.vertices), 3);
+        bind_attribute(gl, attributes.normal, self.normals.as_ref(), 3);
+        bind_attribute(gl, attributes.color, self.colors.as_ref(), 3);
+        bind_attribute(gl, attributes.uv, self.uv.as_ref(), 2);
+        gl.bind_buffer(GL::ELEMENT_ARRAY_BUFFER, self.indices.as_ref());
+    }
+
+    pub fn draw(&self, gl: &GL) {
+        match self.indices {
+            Some(_) => {
+                gl.draw_elements_with_i32(self.primitive.mode(), self.count, GL::UNSIGNED_SHORT, 0)
+            }
+            None => gl.draw_arrays(self.primitive.mode(), 0, self.count),
+        }
+    }
+}
+
+impl Drop for GpuMesh {
+    fn drop(&mut self) {
+        self.gl.delete_buffer(Some(&self.vertices));
+        for buffer in [&self.normals, &self.colors, &self.uv, &self.indices] {
+            self.gl.delete_buffer(buffer.as_ref());
+        }
+    }
+}
+
+fn bind_attribute(gl: &GL, location: Option<u32>, buffer: Option<&WebGlBuffer>, size: i32) {
+    let location = match location {
+        Some(location) => location,
+        None => return,
+    };
+    match buffer {
+        Some(buffer) => {
+            gl.bind_buffer(GL::ARRAY_BUFFER, Some(buffer));
+            gl.vertex_attrib_pointer_with_i32(location, size, GL::FLOAT, false, 0, 0);
+            gl.enable_vertex_attrib_array(location);
+        }
+        None => gl.disable_vertex_attrib_array(location),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_validate_catches_mismatched_attributes() {
+        let mut mesh = Mesh {
+            vertices: vec![0., 0., 0., 1., 0., 0., 0., 1., 0.],
+            indices: vec![0, 1, 2],
+            ..Default::default()
+        };
+        assert!(mesh.validate().is_ok());
+
+        mesh.colors = vec![1., 1., 1.];
+        assert!(mesh.validate().is_err());
+        mesh.colors = vec![1.; 9];
+        assert!(mesh.validate().is_ok());
+
+        mesh.indices.push(3);
+        assert!(mesh.validate().is_err());
+    }
+}
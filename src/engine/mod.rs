@@ -1,10 +1,20 @@
+pub mod assets;
+pub mod game_loop;
+pub mod helper;
+pub mod material;
+pub mod mesh;
+pub mod renderer;
+pub mod scene;
+
 use std::{cell::Cell, rc::Rc};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use wasm_bindgen::{prelude::Closure, JsCast, JsValue};
 use web_sys::HtmlImageElement;
 
 use crate::browser::new_image;
+
+/// Resolves once image is ready to be used, or with error if browser could not load it.
 pub async fn load_image(src: impl AsRef<str>) -> Result<HtmlImageElement> {
     let image = new_image()?;
 
@@ -29,6 +39,13 @@ pub async fn load_image(src: impl AsRef<str>) -> Result<HtmlImageElement> {
     image.set_onerror(Some(error_callback.as_ref().unchecked_ref()));
     image.set_src(src.as_ref());
 
-    let _ = receiver.await;
-    Ok(image)
+    match receiver.await {
+        Ok(Ok(())) => Ok(image),
+        Ok(Err(err)) => Err(anyhow!(format!(
+            "Failed to load image {:?} with error {:#?}",
+            src.as_ref(),
+            err
+        ))),
+        Err(_) => Err(anyhow!("Loading image {:?} was cancelled", src.as_ref())),
+    }
 }
@@ -0,0 +1,90 @@
+use std::rc::Rc;
+
+use nalgebra::Matrix4;
+use web_sys::WebGlRenderingContext as GL;
+
+use super::material::{Material, Uniform};
+use super::mesh::GpuMesh;
+use super::scene::Node;
+
+// uniforms renderer sets for every node with a mesh, when material has them.
+pub const MVP_MATRIX: &str = "u_MvpMatrix";
+pub const MODEL_MATRIX: &str = "u_ModelMatrix";
+pub const NORMAL_MATRIX: &str = "u_NormalMatrix";
+pub const LIGHT_MVP_MATRIX: &str = "u_LightMvpMatrix";
+
+/// Walks a scene and draws every visible node having a mesh.
+pub struct Renderer {
+    view_projection: Matrix4<f32>,
+    light_view_projection: Option<Matrix4<f32>>,
+}
+
+impl Renderer {
+    pub fn new(view_projection: Matrix4<f32>) -> Self {
+        Renderer {
+            view_projection,
+            light_view_projection: None,
+        }
+    }
+
+    /// View of scene from a light, for materials looking up a shadow map.
+    pub fn with_light_view_projection(mut self, light_view_projection: Matrix4<f32>) -> Self {
+        self.light_view_projection = Some(light_view_projection);
+        self
+    }
+
+    /// Material is put in use, and uniforms other than per node ones are left to caller.
+    pub fn render(&self, gl: &GL, material: &Material, root: &Node) {
+        self.walk(gl, material, root, false);
+    }
+
+    /// Draws only nodes casting shadow, typically into a shadow map.
+    pub fn render_shadow_casters(&self, gl: &GL, material: &Material, root: &Node) {
+        self.walk(gl, material, root, true);
+    }
+
+    fn walk(&self, gl: &GL, material: &Material, root: &Node, only_casters: bool) {
+        material.use_program(gl);
+        // nodes often share a mesh, like tokens, no need to bind it again.
+        let mut bound: Option<Rc<GpuMesh>> = None;
+        root.visit(&Matrix4::identity(), &mut |node, world| {
+            for (name, value) in node.uniforms() {
+                material.set(gl, name, value);
+            }
+            if let Some(texture) = &node.texture {
+                gl.active_texture(GL::TEXTURE0);
+                gl.bind_texture(GL::TEXTURE_2D, Some(texture));
+            }
+            let mesh = match &node.mesh {
+                Some(mesh) if node.casts_shadow || !only_casters => mesh,
+                _ => return,
+            };
+            self.set_matrices(gl, material, world);
+            if !matches!(&bound, Some(bound) if Rc::ptr_eq(bound, mesh)) {
+                mesh.bind(gl, material);
+                bound = Some(mesh.clone());
+            }
+            mesh.draw(gl);
+        });
+    }
+
+    fn set_matrices(&self, gl: &GL, material: &Material, model: &Matrix4<f32>) {
+        material.set(gl, MVP_MATRIX, &Uniform::Mat4(self.view_projection * model));
+        material.set(gl, MODEL_MATRIX, &Uniform::Mat4(*model));
+        if material.has_uniform(NORMAL_MATRIX) {
+            // inverse transpose keeps normals perpendicular to surface under non uniform scale.
+            let normal_matrix = model
+                .try_inverse()
+                .map(|inverse| inverse.transpose())
+                .unwrap_or_else(Matrix4::identity);
+            material.set(gl, NORMAL_MATRIX, &Uniform::Mat4(normal_matrix));
+        }
+        if let Some(light_view_projection) = &self.light_view_projection {
+            material.set(
+                gl,
+                LIGHT_MVP_MATRIX,
+                &Uniform::Mat4(light_view_projection * model),
+            );
+        }
+    }
+}
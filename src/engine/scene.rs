@@ -0,0 +1,119 @@
+use std::rc::Rc;
+
+use nalgebra::Matrix4;
+use web_sys::WebGlTexture;
+
+use super::material::Uniform;
+use super::mesh::GpuMesh;
+
+/// Part of a scene placed relative to its parent. Node without mesh only groups its children.
+pub struct Node {
+    pub transform: Matrix4<f32>,
+    pub mesh: Option<Rc<GpuMesh>>,
+    pub visible: bool,
+    pub casts_shadow: bool,
+    // bound to unit 0 when node is reached, and like uniforms stays bound for later nodes.
+    pub texture: Option<WebGlTexture>,
+    pub children: Vec<Node>,
+    // set when node is reached, and stay in effect until some later node sets them again.
+    uniforms: Vec<(String, Uniform)>,
+}
+
+impl Default for Node {
+    fn default() -> Self {
+        Node {
+            transform: Matrix4::identity(),
+            mesh: None,
+            visible: true,
+            casts_shadow: false,
+            texture: None,
+            children: Vec::new(),
+            uniforms: Vec::new(),
+        }
+    }
+}
+
+impl Node {
+    pub fn group() -> Self {
+        Node::default()
+    }
+
+    pub fn with_mesh(mesh: Rc<GpuMesh>) -> Self {
+        Node {
+            mesh: Some(mesh),
+            ..Default::default()
+        }
+    }
+
+    /// Returns index of child, to get back to it with `child_mut`.
+    pub fn add_child(&mut self, child: Node) -> usize {
+        self.children.push(child);
+        self.children.len() - 1
+    }
+
+    pub fn child_mut(&mut self, index: usize) -> Option<&mut Node> {
+        self.children.get_mut(index)
+    }
+
+    pub fn set_uniform(&mut self, name: &str, value: Uniform) {
+        match self
+            .uniforms
+            .iter_mut()
+            .find(|(existing, _)| existing == name)
+        {
+            Some((_, existing)) => *existing = value,
+            None => self.uniforms.push((name.to_owned(), value)),
+        }
+    }
+
+    pub fn uniforms(&self) -> &[(String, Uniform)] {
+        &self.uniforms
+    }
+
+    /// Calls back every visible node, parents before children, along with its world transform.
+    /// Children of hidden node are hidden too.
+    pub fn visit(&self, parent: &Matrix4<f32>, callback: &mut impl FnMut(&Node, &Matrix4<f32>)) {
+        if !self.visible {
+            return;
+        }
+        let world = parent * self.transform;
+        callback(self, &world);
+        for child in &self.children {
+            child.visit(&world, callback);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use nalgebra::{Point3, Vector3};
+
+    use super::*;
+
+    #[test]
+    fn test_children_are_placed_relative_to_parent() {
+        let mut root = Node::group();
+        root.transform = Matrix4::new_translation(&Vector3::new(1., 0., 0.));
+        let mut child = Node::group();
+        child.transform = Matrix4::new_translation(&Vector3::new(0., 2., 0.));
+        child.add_child(Node::group());
+        let mut hidden = Node::group();
+        hidden.visible = false;
+        hidden.add_child(Node::group());
+        root.add_child(child);
+        root.add_child(hidden);
+
+        let mut origins = Vec::new();
+        root.visit(&Matrix4::identity(), &mut |_, world| {
+            origins.push(world.transform_point(&Point3::origin()));
+        });
+        assert_eq!(
+            origins,
+            vec![
+                Point3::new(1., 0., 0.),
+                Point3::new(1., 2., 0.),
+                Point3::new(1., 2., 0.),
+            ]
+        );
+    }
+}
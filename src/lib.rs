@@ -1,14 +1,44 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
 
-use browser::{button, canvas, context, height, spawn_local, width, window};
-use programs::ludo::ludo_program::LudoProgram;
+use browser::{
+    add_event_listener, button, canvas, context, device_pixel_ratio, fetch_text, height, log, now,
+    query_param, spawn_local, width, window,
+};
+use controls::attach_camera_controls;
+use engine::{
+    assets::Manifest,
+    game_loop::{GameLoop, STEP},
+};
+use lobby::Lobby;
+use network::{server_url, wire_encoding, Connection};
+use play::Play;
+use programs::ludo::{
+    camera::Camera, color::Color, ludo_program::LudoProgram, ludo_state::LudoState,
+    shadow_map::ShadowQuality, theme::Theme,
+};
+use programs::texture_program::TextureProgram;
+use server::entity::action::GameEvent;
+use theme_picker::ThemePicker;
 use wasm_bindgen::prelude::*;
+use web_sys::{MouseEvent, WebGlRenderingContext};
 
 mod browser;
+mod controls;
 mod engine;
+mod lobby;
+mod network;
+mod offline;
+mod play;
 mod programs;
 mod server;
 mod shaders;
+mod theme_picker;
+
+// textures and themes to load at start up, relative to page.
+const ASSET_MANIFEST: &str = "assets.json";
 
 // When the `wee_alloc` feature is enabled, this uses `wee_alloc` as the global
 // allocator.
@@ -32,59 +62,267 @@ pub fn main_js() -> Result<(), JsValue> {
     // It's disabled in release mode so it doesn't bloat up the file size.
     #[cfg(debug_assertions)]
     console_error_panic_hook::set_once();
-    set_canvas_size().unwrap();
-
     let dice_button = button("roll-dice").unwrap();
+    let token_buttons: Vec<_> = (1..=4)
+        .map(|token_id| button(format!("move-token-{}", token_id)).unwrap())
+        .collect();
 
     let gl = context().unwrap();
-    let ludo_program = LudoProgram::new(&gl);
+    let aspect = resize_canvas(&gl).unwrap();
+    // weak devices can turn shadows off with `?shadows=off`.
+    let shadow_quality = query_param("shadows")
+        .ok()
+        .flatten()
+        .and_then(|name| ShadowQuality::from_name(&name))
+        .unwrap_or(ShadowQuality::High);
+    // shapes on tokens and home columns are on unless page has `?markings=off`.
+    let markings = query_param("markings").ok().flatten().as_deref() != Some("off");
+    let mut ludo_program = LudoProgram::new(&gl, shadow_quality, Theme::default(), markings);
+    let assets = ludo_program.assets();
 
-    gl.use_program(Some(&ludo_program.program));
-    ludo_program
-        .render(&gl, crate::programs::ludo::color::Color::Yellow, 45.)
-        .unwrap();
+    let canvas = canvas().unwrap();
+    let camera = Rc::new(RefCell::new(Camera::new(aspect)));
+    // start from a neutral side and turn board towards local player.
+    camera.borrow_mut().look_from(&Color::Yellow);
 
-    let mut angle = 45.;
     let animation_loop = Rc::new(RefCell::new(None));
-    let animation_loop_cloned = animation_loop.clone();
-    *animation_loop_cloned.borrow_mut() = Some(Closure::new(move || {
-        angle = angle % 360.;
-        let gl = context().unwrap();
-        if let Err(err) =
-            ludo_program.render(&gl, crate::programs::ludo::color::Color::Yellow, angle)
-        {
-            web_sys::console::log_1(&format!("Failed with error {:#?}", err).into());
-        }
-
-        angle += 0.5;
-        if angle < 90. {
-            request_animation_frame(animation_loop.borrow().as_ref().unwrap());
-        } else {
-            angle = 45.;
-            ludo_program
-                .render(&gl, crate::programs::ludo::color::Color::Yellow, angle)
-                .unwrap();
-        }
-    }));
-    let button_down_cb = Closure::<dyn FnMut()>::new(move || {
-        request_animation_frame(animation_loop_cloned.borrow().as_ref().unwrap());
-    });
-    dice_button
-        .add_event_listener_with_callback("mousedown", button_down_cb.as_ref().unchecked_ref())
+    let frame_pending = Rc::new(Cell::new(false));
+    let request_redraw: Rc<dyn Fn()> = {
+        let animation_loop = animation_loop.clone();
+        let frame_pending = frame_pending.clone();
+        Rc::new(move || {
+            if !frame_pending.replace(true) {
+                request_animation_frame(animation_loop.borrow().as_ref().unwrap());
+            }
+        })
+    };
+
+    // game events change it, every frame draws it.
+    let state = Rc::new(RefCell::new(LudoState::default()));
+    let play = Rc::new(RefCell::new(None::<Play>));
+    // theme picked by player, board is rebuilt with it on next frame.
+    let next_theme = Rc::new(RefCell::new(None::<Theme>));
+    {
+        let camera = camera.clone();
+        let next_theme = next_theme.clone();
+        let state = state.clone();
+        let play = play.clone();
+        let request_redraw = request_redraw.clone();
+        let mut game_loop = GameLoop::default();
+        // same closure is used for every frame, frames are only asked for while something moves.
+        *animation_loop.borrow_mut() = Some(Closure::new(move || {
+            frame_pending.set(false);
+            let now = now().unwrap();
+            let (steps, alpha) = game_loop.advance(now);
+            for _ in 0..steps {
+                camera.borrow_mut().update();
+                state.borrow_mut().tick((STEP / 1000.) as f32);
+            }
+            let animating = state.borrow().is_animating();
+            // bots wait for board to settle down, so that their moves can be followed.
+            let play = play.borrow().clone();
+            let bots_waiting = match play {
+                Some(Play::Offline(game)) => {
+                    game.tick_bots(now, !animating).unwrap_or_else(|err| {
+                        log!("Bot failed to play {:#?}", err);
+                        false
+                    })
+                }
+                _ => false,
+            };
+
+            let gl = context().unwrap();
+            if let Some(theme) = next_theme.borrow_mut().take() {
+                if let Err(err) = ludo_program.set_theme(&gl, theme) {
+                    log!("Failed to change theme {:#?}", err);
+                }
+            }
+            if let Err(err) = ludo_program.render(&gl, &camera.borrow(), &state.borrow(), alpha) {
+                web_sys::console::log_1(&format!("Failed with error {:#?}", err).into());
+            }
+
+            if animating || bots_waiting || camera.borrow().is_animating() {
+                request_redraw();
+            } else {
+                game_loop.pause();
+            }
+        }));
+    }
+
+    attach_camera_controls(&canvas, camera.clone(), request_redraw.clone()).unwrap();
+    {
+        let camera = camera.clone();
+        let request_redraw = request_redraw.clone();
+        // rotating a phone also resizes window.
+        add_event_listener(&window().unwrap(), "resize", move |_: web_sys::Event| {
+            match resize_canvas(&context().unwrap()) {
+                Ok(aspect) => camera.borrow_mut().set_aspect(aspect),
+                Err(err) => log!("Failed to resize canvas {:#?}", err),
+            }
+            request_redraw();
+        })
         .unwrap();
-    button_down_cb.forget();
+    }
+
+    // events of online and offline games take same path to screen.
+    let apply_event: Rc<dyn Fn(GameEvent)> = {
+        let state = state.clone();
+        let camera = camera.clone();
+        let request_redraw = request_redraw.clone();
+        let token_buttons = token_buttons.clone();
+        Rc::new(move |event: GameEvent| {
+            log!("{:?}", event);
+            state.borrow_mut().apply(&event);
+            if let GameEvent::DiceRevealed { server_seed } = &event {
+                match state.borrow().verify_dice(server_seed) {
+                    Ok(()) => log!("Every roll of game matches seeds it was committed to"),
+                    Err(err) => log!("Dice of game can not be trusted: {}", err),
+                }
+            }
+            // only tokens server lists as legal moves can be picked.
+            for (token_id, token_button) in (1..=4).zip(&token_buttons) {
+                token_button.set_disabled(!state.borrow().can_move(token_id));
+            }
+            // when device is passed around, board turns towards whoever plays next.
+            if let GameEvent::TurnChanged { color } = &event {
+                if state.borrow().is_local(color) {
+                    camera.borrow_mut().look_from(&color.into());
+                }
+            }
+            request_redraw();
+        })
+    };
 
+    let lobby = {
+        let play = play.clone();
+        let request_redraw = request_redraw.clone();
+        Lobby::new(state, apply_event.clone(), move |started: Play| {
+            if let Play::Online(session) = &started {
+                camera.borrow_mut().look_from(&(&session.color).into());
+            }
+            *play.borrow_mut() = Some(started);
+            request_redraw();
+        })
+        .unwrap()
+    };
+    let theme_picker = {
+        let lobby = lobby.clone();
+        let request_redraw = request_redraw.clone();
+        ThemePicker::new(move |theme: Theme| {
+            lobby.set_theme(theme.clone());
+            *next_theme.borrow_mut() = Some(theme);
+            request_redraw();
+        })
+        .unwrap()
+    };
+    {
+        let lobby = lobby.clone();
+        let gl = gl.clone();
+        spawn_local(async move {
+            let loaded = async {
+                let manifest = Manifest::from_json(&fetch_text(ASSET_MANIFEST).await?)?;
+                // one broken theme file leaves rest of themes and textures usable.
+                for src in &manifest.themes {
+                    match async { Theme::from_json(&fetch_text(src).await?) }.await {
+                        Ok(theme) => theme_picker.add(theme)?,
+                        Err(err) => log!("Failed to load theme {:?} {:#?}", src, err),
+                    }
+                }
+                let texture_program = TextureProgram::new(&gl);
+                assets
+                    .load(&gl, &texture_program, &manifest, |progress| {
+                        lobby.show_progress(progress)
+                    })
+                    .await
+            };
+            if let Err(err) = loaded.await {
+                log!("{:#?}", err);
+                lobby.show_message(&format!("{}", err));
+            }
+        });
+    }
+    {
+        let lobby = lobby.clone();
+        spawn_local(async move {
+            let on_event = {
+                let lobby = lobby.clone();
+                move |_, event: GameEvent| {
+                    apply_event(event);
+                    lobby.update();
+                }
+            };
+            let connection = async {
+                let url = server_url()?;
+                Connection::connect(&url, wire_encoding()?, on_event).await
+            }
+            .await;
+            match connection {
+                Ok(connection) => lobby.set_connection(connection),
+                Err(err) => lobby.show_message(&format!("Failed to connect {}", err)),
+            }
+        });
+    }
+
+    {
+        let play = play.clone();
+        // dice spins once game tells everyone what was rolled.
+        add_event_listener(&dice_button, "mousedown", move |_: MouseEvent| {
+            if let Some(play) = play.borrow().clone() {
+                spawn_local(async move {
+                    if let Err(err) = play.roll_dice().await {
+                        log!("Failed to roll dice {:#?}", err);
+                    }
+                });
+            }
+        })
+        .unwrap();
+    }
+    for (token_id, token_button) in (1..=4).zip(&token_buttons) {
+        let play = play.clone();
+        token_button.set_disabled(true);
+        add_event_listener(token_button, "mousedown", move |_: MouseEvent| {
+            if let Some(play) = play.borrow().clone() {
+                spawn_local(async move {
+                    if let Err(err) = play.move_token(token_id).await {
+                        log!("Failed to move token {:#?}", err);
+                    }
+                });
+            }
+        })
+        .unwrap();
+    }
+
+    request_redraw();
     Ok(())
 }
 
-fn set_canvas_size() -> anyhow::Result<u32> {
-    let canvas = canvas().unwrap();
+/// Checks dice of a completed game from page, with seeds and rolls it has logged.
+#[wasm_bindgen(js_name = verifyDice)]
+pub fn verify_dice(
+    seed_hash: &str,
+    server_seed: &str,
+    client_seeds: Vec<String>,
+    rolls: Vec<u8>,
+) -> Result<(), JsValue> {
+    server::entity::fair_dice::verify(seed_hash, server_seed, &client_seeds, &rolls)
+        .map_err(|err| JsValue::from_str(&err.to_string()))
+}
 
-    let height = height()?;
-    let widht = width()?;
-    let size = widht.min(height);
-    canvas.set_height(size);
-    canvas.set_width(size);
+/// Canvas fills window, and draws one pixel for every device pixel so that it is sharp on phones.
+/// Returns width over height of canvas.
+fn resize_canvas(gl: &WebGlRenderingContext) -> anyhow::Result<f32> {
+    let canvas = canvas()?;
+    let (width, height) = (width()?, height()?);
+    let style = canvas.style();
+    let px = |size: u32| format!("{}px", size);
+    style
+        .set_property("width", &px(width))
+        .and_then(|_| style.set_property("height", &px(height)))
+        .map_err(|err| anyhow::anyhow!(format!("Failed to size canvas {:#?}", err)))?;
 
-    Ok(size)
+    let ratio = device_pixel_ratio()?;
+    canvas.set_width((width as f64 * ratio).round() as u32);
+    canvas.set_height((height as f64 * ratio).round() as u32);
+    gl.viewport(0, 0, canvas.width() as i32, canvas.height() as i32);
+    Ok(width as f32 / height.max(1) as f32)
 }
@@ -0,0 +1,324 @@
+use std::{cell::RefCell, rc::Rc};
+
+use anyhow::{anyhow, Result};
+use web_sys::{Element, HtmlButtonElement, HtmlElement, HtmlInputElement};
+
+use crate::browser::{
+    append_button, append_element, append_input, append_select, body, clear, log, on_click,
+    query_param, spawn_local,
+};
+use crate::engine::assets::Progress;
+use crate::network::{Connection, Session};
+use crate::offline::OfflineGame;
+use crate::play::Play;
+use crate::programs::ludo::{color::Color as BoardColor, ludo_state::LudoState, theme::Theme};
+use crate::server::entity::{action::GameEvent, bot::Difficulty, color::Color};
+
+const PANEL_STYLE: &str =
+    "position: absolute; top: 1em; left: 1em; padding: 1em; background: rgba(255, 255, 255, 0.9);";
+
+// players sharing one device.
+const OFFLINE_PLAYERS: [usize; 3] = [2, 3, 4];
+const BOTS: [usize; 3] = [1, 2, 3];
+// milliseconds a bot waits before it acts, `?bot_delay=` changes it.
+const BOT_DELAY: f64 = 800.;
+
+/// Panel over the board, where player creates or joins a game, picks a color and starts it.
+/// Without a server, same device can be passed around by all players.
+pub struct Lobby {
+    panel: HtmlElement,
+    code: HtmlInputElement,
+    colors: Element,
+    players: Element,
+    message: Element,
+    loading: Element,
+    start: HtmlButtonElement,
+    connection: RefCell<Option<Connection>>,
+    session: RefCell<Option<Session>>,
+    state: Rc<RefCell<LudoState>>,
+    // color buttons are painted like tokens on board.
+    theme: RefCell<Theme>,
+    // offline game hands its events here, just like server does.
+    on_event: Rc<dyn Fn(GameEvent)>,
+    // called once this browser has a game to play.
+    on_play: Box<dyn Fn(Play)>,
+}
+
+impl Lobby {
+    pub fn new(
+        state: Rc<RefCell<LudoState>>,
+        on_event: Rc<dyn Fn(GameEvent)>,
+        on_play: impl Fn(Play) + 'static,
+    ) -> Result<Rc<Self>> {
+        let body = body()?;
+        let panel = append_element::<HtmlElement>(&body, "div", "")?;
+        panel.set_attribute("style", PANEL_STYLE).ok();
+        let create = append_button(&panel, "Create Game")?;
+        let code = append_input(&panel, "Join code")?;
+        let join = append_button(&panel, "Join")?;
+        let colors = append_element::<Element>(&panel, "div", "")?;
+        let players = append_element::<Element>(&panel, "ul", "")?;
+        let start = append_button(&panel, "Start Game")?;
+        start.set_disabled(true);
+        let message = append_element::<Element>(&panel, "p", "Connecting...")?;
+        let loading = append_element::<Element>(&panel, "p", "")?;
+        append_element::<Element>(&panel, "p", "Or pass this device around")?;
+        let offline: Vec<(usize, HtmlButtonElement)> = OFFLINE_PLAYERS
+            .iter()
+            .map(|players| {
+                Ok((
+                    *players,
+                    append_button(&panel, &format!("{} Players", players))?,
+                ))
+            })
+            .collect::<Result<_>>()?;
+        append_element::<Element>(&panel, "p", "Or play against computer")?;
+        let difficulty = append_select(
+            &panel,
+            &Difficulty::ALL
+                .iter()
+                .map(|difficulty| format!("{:?}", difficulty))
+                .collect::<Vec<_>>(),
+        )?;
+        let bots: Vec<(usize, HtmlButtonElement)> = BOTS
+            .iter()
+            .map(|bots| {
+                let label = if *bots == 1 {
+                    "1 Bot".to_owned()
+                } else {
+                    format!("{} Bots", bots)
+                };
+                Ok((*bots, append_button(&panel, &label)?))
+            })
+            .collect::<Result<_>>()?;
+        // link shared with friends carries join code.
+        if let Some(game) = query_param("game")? {
+            code.set_value(&game);
+        }
+
+        let lobby = Rc::new(Lobby {
+            panel,
+            code,
+            colors,
+            players,
+            message,
+            loading,
+            start,
+            connection: RefCell::new(None),
+            session: RefCell::new(None),
+            state,
+            theme: RefCell::default(),
+            on_event,
+            on_play: Box::new(on_play),
+        });
+
+        for (bots, button) in bots {
+            let lobby = lobby.clone();
+            let difficulty = difficulty.clone();
+            on_click(&button, move || {
+                let difficulty =
+                    Difficulty::from_name(&difficulty.value()).unwrap_or(Difficulty::Normal);
+                if let Err(err) = lobby.play_against_bots(bots, difficulty) {
+                    lobby.show_message(&format!("Failed to start game {}", err));
+                }
+            })?;
+        }
+        for (players, button) in offline {
+            let lobby = lobby.clone();
+            on_click(&button, move || {
+                if let Err(err) = lobby.play_offline(players) {
+                    lobby.show_message(&format!("Failed to start game {}", err));
+                }
+            })?;
+        }
+
+        {
+            let lobby = lobby.clone();
+            on_click(&create, move || {
+                let lobby = lobby.clone();
+                spawn_local(async move {
+                    if let Err(err) = lobby.create_game().await {
+                        lobby.show_message(&format!("Failed to create game {}", err));
+                    }
+                });
+            })?;
+        }
+        {
+            let lobby = lobby.clone();
+            on_click(&join, move || {
+                let lobby = lobby.clone();
+                spawn_local(async move {
+                    let game_id = match lobby.code.value().trim().parse() {
+                        Ok(game_id) => game_id,
+                        Err(_) => return lobby.show_message("Join code is a number"),
+                    };
+                    if let Err(err) = lobby.show_colors(game_id).await {
+                        lobby.show_message(&format!("Failed to find game {}", err));
+                    }
+                });
+            })?;
+        }
+        {
+            let start = lobby.start.clone();
+            let lobby = lobby.clone();
+            on_click(&start, move || {
+                let lobby = lobby.clone();
+                let session = lobby.session.borrow().clone();
+                if let Some(session) = session {
+                    spawn_local(async move {
+                        if let Err(err) = session.start().await {
+                            lobby.show_message(&format!("Failed to start game {}", err));
+                        }
+                    });
+                }
+            })?;
+        }
+        Ok(lobby)
+    }
+
+    pub fn set_connection(&self, connection: Connection) {
+        *self.connection.borrow_mut() = Some(connection);
+        self.show_message("Create a game, or enter a join code");
+    }
+
+    pub fn set_theme(&self, theme: Theme) {
+        *self.theme.borrow_mut() = theme;
+    }
+
+    pub fn show_message(&self, message: &str) {
+        self.message.set_text_content(Some(message));
+    }
+
+    /// Loading screen line, goes away once everything is loaded.
+    pub fn show_progress(&self, progress: Progress) {
+        let Progress { loaded, total } = progress;
+        self.loading
+            .set_text_content(Some(&format!("Loading {} / {}", loaded, total)));
+        self.loading
+            .set_attribute(
+                "style",
+                if loaded == total {
+                    "display: none;"
+                } else {
+                    ""
+                },
+            )
+            .ok();
+    }
+
+    /// Shows who is seated, lobby goes away once game starts.
+    pub fn update(&self) {
+        let state = self.state.borrow();
+        if state.has_started() {
+            self.panel.set_hidden(true);
+            return;
+        }
+        clear(&self.players);
+        for color in state.players() {
+            let label = if state.is_local(color) {
+                format!("{:?} (you)", color)
+            } else {
+                format!("{:?}", color)
+            };
+            if let Err(err) = append_element::<Element>(&self.players, "li", &label) {
+                log!("Failed to show player {:#?}", err);
+            }
+        }
+        self.start
+            .set_disabled(self.session.borrow().is_none() || state.players().len() < 2);
+    }
+
+    async fn create_game(self: &Rc<Self>) -> Result<()> {
+        let game_id = self.connection()?.create_game().await?;
+        self.code.set_value(&game_id.to_string());
+        self.show_colors(game_id).await
+    }
+
+    // a button for every color still free, picking one joins game.
+    async fn show_colors(self: &Rc<Self>, game_id: u32) -> Result<()> {
+        let colors = self.connection()?.available_colors(game_id).await?;
+        clear(&self.colors);
+        if colors.is_empty() {
+            self.show_message(&format!("Game {} can not be joined", game_id));
+            return Ok(());
+        }
+        self.show_message(&format!("Pick a color for game {}", game_id));
+        for color in colors.iter() {
+            let button = append_button(&self.colors, &format!("{:?}", color))?;
+            let [r, g, b] = self.theme.borrow().color(&BoardColor::from(color));
+            let style = format!("background: rgb({}, {}, {});", r * 255., g * 255., b * 255.);
+            button.set_attribute("style", &style).ok();
+
+            let lobby = self.clone();
+            let color = color.clone();
+            let seated: Vec<Color> = Color::ALL
+                .iter()
+                .filter(|seated| !colors.contains(seated))
+                .cloned()
+                .collect();
+            on_click(&button, move || {
+                let lobby = lobby.clone();
+                let color = color.clone();
+                let seated = seated.clone();
+                spawn_local(async move {
+                    if let Err(err) = lobby.join(game_id, color, seated).await {
+                        lobby.show_message(&format!("Failed to join game {}", err));
+                    }
+                });
+            })?;
+        }
+        Ok(())
+    }
+
+    async fn join(&self, game_id: u32, color: Color, seated: Vec<Color>) -> Result<()> {
+        let session = Session::join(self.connection()?, game_id, color).await?;
+        clear(&self.colors);
+        self.show_message(&format!(
+            "Playing game {} as {:?}, share join code with friends",
+            game_id, session.color
+        ));
+        {
+            let mut state = self.state.borrow_mut();
+            state.seat_local(session.color.clone());
+            // players seated before this one were announced before it started listening.
+            for color in seated {
+                state.apply(&GameEvent::PlayerJoined { color });
+            }
+        }
+        *self.session.borrow_mut() = Some(session.clone());
+        (self.on_play)(Play::Online(session));
+        self.update();
+        Ok(())
+    }
+
+    fn play_offline(&self, players: usize) -> Result<()> {
+        for color in Color::ALL.iter().take(players) {
+            self.state.borrow_mut().seat_local(color.clone());
+        }
+        let on_event = self.on_event.clone();
+        let game = OfflineGame::start(players, move |event| on_event(event))?;
+        (self.on_play)(Play::Offline(Rc::new(game)));
+        self.update();
+        Ok(())
+    }
+
+    fn play_against_bots(&self, bots: usize, difficulty: Difficulty) -> Result<()> {
+        let delay = query_param("bot_delay")?
+            .and_then(|delay| delay.parse().ok())
+            .unwrap_or(BOT_DELAY);
+        self.state.borrow_mut().seat_local(Color::Yellow);
+        let on_event = self.on_event.clone();
+        let game =
+            OfflineGame::against_bots(bots, difficulty, delay, move |event| on_event(event))?;
+        (self.on_play)(Play::Offline(Rc::new(game)));
+        self.update();
+        Ok(())
+    }
+
+    fn connection(&self) -> Result<Connection> {
+        self.connection
+            .borrow()
+            .clone()
+            .ok_or_else(|| anyhow!("Not connected to server yet"))
+    }
+}
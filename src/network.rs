@@ -0,0 +1,347 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::Rc,
+};
+
+use anyhow::{anyhow, Result};
+use futures::channel::oneshot;
+use rand::RngCore;
+use wasm_bindgen::{prelude::Closure, JsCast};
+use web_sys::{BinaryType, Event, MessageEvent, WebSocket};
+
+use crate::browser::{log, query_param, sleep, spawn_local, window};
+use crate::server::entity::{
+    action::{Command, GameEvent, Reply, Request, Response, PROTOCOL_VERSION},
+    color::Color,
+    encoding::Encoding,
+    error::Failure,
+};
+
+// name client introduces itself with.
+const CLIENT_NAME: &str = concat!("ludo web ", env!("CARGO_PKG_VERSION"));
+
+// times client tries to get back to a restarting server, waiting as long as server asked before each.
+const RECONNECT_ATTEMPTS: u32 = 5;
+
+/// Connection to game server.
+/// Replies find their request by its id, events pushed by server go to `on_event`.
+/// When server shuts down, connection opens again once server is back and rejoins game it plays in.
+#[derive(Clone)]
+pub struct Connection {
+    url: Rc<str>,
+    // replaced by a new one after server restarts, none while closed.
+    socket: Rc<RefCell<Option<WebSocket>>>,
+    // waiting for reply, by id of request.
+    pending: Rc<RefCell<HashMap<u32, oneshot::Sender<Response>>>>,
+    next_request_id: Rc<Cell<u32>>,
+    // json until server has answered hello.
+    encoding: Rc<Cell<Encoding>>,
+    // asked for in hello.
+    wire_encoding: Encoding,
+    on_event: Rc<RefCell<dyn FnMut(u32, GameEvent)>>,
+    seat: Rc<RefCell<Option<Seat>>>,
+}
+
+// what server needs to give seat back to player.
+struct Seat {
+    game_id: u32,
+    color: Color,
+    rejoin_token: String,
+}
+
+impl Connection {
+    /// Resolves once socket is open and server has answered hello, which asks for given encoding.
+    pub async fn connect(
+        url: &str,
+        encoding: Encoding,
+        on_event: impl FnMut(u32, GameEvent) + 'static,
+    ) -> Result<Self> {
+        let connection = Connection {
+            url: url.into(),
+            socket: Rc::default(),
+            pending: Rc::default(),
+            next_request_id: Rc::new(Cell::new(1)),
+            encoding: Rc::new(Cell::new(Encoding::Json)),
+            wire_encoding: encoding,
+            on_event: Rc::new(RefCell::new(on_event)),
+            seat: Rc::default(),
+        };
+        connection.open().await?;
+        Ok(connection)
+    }
+
+    // opens a new socket and says hello on it.
+    async fn open(&self) -> Result<()> {
+        let url = &*self.url;
+        let socket = WebSocket::new(url)
+            .map_err(|err| anyhow!(format!("Failed to open socket to {} {:#?}", url, err)))?;
+        socket.set_binary_type(BinaryType::Arraybuffer);
+
+        let on_message = {
+            let connection = self.clone();
+            Closure::<dyn FnMut(MessageEvent)>::new(move |event: MessageEvent| {
+                connection.receive(event)
+            })
+        };
+        socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        on_message.forget();
+
+        let on_close = {
+            let (connection, closed) = (self.clone(), socket.clone());
+            Closure::<dyn FnMut(Event)>::new(move |_| {
+                // socket of an earlier server may close after a new one has been opened.
+                let mut socket = connection.socket.borrow_mut();
+                if socket.as_ref() == Some(&closed) {
+                    *socket = None;
+                }
+                // dropping senders fails every request still waiting.
+                connection.pending.borrow_mut().clear()
+            })
+        };
+        socket.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+        on_close.forget();
+
+        let (sender, receiver) = oneshot::channel::<bool>();
+        let open_tx = Rc::new(Cell::new(Some(sender)));
+        let error_tx = open_tx.clone();
+        let on_open = Closure::once(move || {
+            if let Some(tx) = open_tx.take() {
+                let _ = tx.send(true);
+            }
+        });
+        let on_error = Closure::<dyn FnMut(Event)>::new(move |_| {
+            if let Some(tx) = error_tx.take() {
+                let _ = tx.send(false);
+            }
+        });
+        socket.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+        socket.set_onerror(Some(on_error.as_ref().unchecked_ref()));
+        on_open.forget();
+        on_error.forget();
+
+        if !receiver.await.unwrap_or(false) {
+            return Err(anyhow!("Failed to connect to {}", url));
+        }
+        *self.socket.borrow_mut() = Some(socket);
+        self.encoding.set(Encoding::Json);
+        self.request(Command::Hello {
+            protocol_version: PROTOCOL_VERSION,
+            client_name: CLIENT_NAME.to_owned(),
+            encoding: self.wire_encoding,
+        })
+        .await?;
+        self.encoding.set(self.wire_encoding);
+        Ok(())
+    }
+
+    fn receive(&self, event: MessageEvent) {
+        let data = event.data();
+        // text frames carry json, binary ones message pack.
+        let reply = match data.as_string() {
+            Some(text) => Encoding::Json.decode::<Reply>(text.as_bytes()),
+            None => Encoding::MessagePack.decode::<Reply>(&js_sys::Uint8Array::new(&data).to_vec()),
+        };
+        match reply {
+            Ok(Reply {
+                response: Response::Event { game_id, event },
+                ..
+            }) => (self.on_event.borrow_mut())(game_id, event),
+            Ok(Reply {
+                response: Response::ServerShuttingDown { reconnect_after },
+                ..
+            }) => {
+                log!(
+                    "Server is shutting down, it should be back in {} ms",
+                    reconnect_after
+                );
+                let connection = self.clone();
+                spawn_local(async move {
+                    if let Err(err) = connection.resume(reconnect_after).await {
+                        log!("Failed to get back to server {:#}", err);
+                    }
+                });
+            }
+            Ok(reply) => {
+                let waiting = reply
+                    .request_id
+                    .and_then(|id| self.pending.borrow_mut().remove(&id));
+                match waiting {
+                    Some(waiting) => {
+                        let _ = waiting.send(reply.response);
+                    }
+                    None => log!("Reply {:?} without request", reply),
+                }
+            }
+            Err(err) => log!("Failed to read message {:#}", err),
+        }
+    }
+
+    // connects again once restarted server is back, and takes back seat player had.
+    async fn resume(&self, reconnect_after: u64) -> Result<()> {
+        let mut attempts = 0;
+        loop {
+            sleep(reconnect_after.min(i32::MAX as u64) as i32).await?;
+            attempts += 1;
+            match self.open().await {
+                Ok(()) => break,
+                Err(err) if attempts < RECONNECT_ATTEMPTS => {
+                    log!("Server is not back yet {:#}", err)
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        let rejoin = self.seat.borrow().as_ref().map(|seat| Command::Rejoin {
+            id: seat.game_id,
+            color: seat.color.clone(),
+            rejoin_token: seat.rejoin_token.clone(),
+        });
+        if let Some(rejoin) = rejoin {
+            self.request(rejoin).await?;
+        }
+        Ok(())
+    }
+
+    /// Sends command and waits for its reply. Failure reported by server comes back as a `Failure`.
+    pub async fn request(&self, command: Command) -> Result<Response> {
+        let request_id = self.next_request_id.get();
+        self.next_request_id.set(request_id.wrapping_add(1));
+        let (sender, receiver) = oneshot::channel();
+        self.pending.borrow_mut().insert(request_id, sender);
+        let request = Request {
+            request_id: Some(request_id),
+            command,
+        };
+        let encoding = self.encoding.get();
+        let message = encoding.encode(&request)?;
+        let sent = match (self.socket.borrow().as_ref(), encoding) {
+            (None, _) => Err("Socket is closed".into()),
+            (Some(socket), Encoding::Json) => socket.send_with_str(&String::from_utf8(message)?),
+            (Some(socket), Encoding::MessagePack) => socket.send_with_u8_array(&message),
+        };
+        if let Err(err) = sent {
+            self.pending.borrow_mut().remove(&request_id);
+            return Err(anyhow!(format!("Failed to send {:?} {:#?}", request, err)));
+        }
+
+        match receiver.await {
+            Ok(Response::Error { code, message }) => Err(Failure::new(code, message).into()),
+            Ok(reply) => Ok(reply),
+            Err(_) => Err(anyhow!(
+                "Connection closed before reply to {:?}",
+                request.command
+            )),
+        }
+    }
+
+    pub async fn create_game(&self) -> Result<u32> {
+        match self
+            .request(Command::CreateGame {
+                time_control: None,
+                untimed: false,
+            })
+            .await?
+        {
+            Response::CreateGameResponse { game_id } => Ok(game_id),
+            other => Err(anyhow!(format!("Unexpected reply {:?}", other))),
+        }
+    }
+
+    pub async fn available_colors(&self, game_id: u32) -> Result<Vec<Color>> {
+        match self
+            .request(Command::AvailableColors { id: game_id })
+            .await?
+        {
+            Response::AvailableColols { colors } => Ok(colors),
+            other => Err(anyhow!(format!("Unexpected reply {:?}", other))),
+        }
+    }
+}
+
+/// Game this browser plays in, as which color.
+#[derive(Clone)]
+pub struct Session {
+    pub connection: Connection,
+    pub game_id: u32,
+    pub color: Color,
+}
+
+impl Session {
+    /// Player adds a random seed to dice, so that server alone can not decide rolls.
+    /// Connection keeps seat, to take it back if server restarts.
+    pub async fn join(connection: Connection, game_id: u32, color: Color) -> Result<Self> {
+        let mut client_seed = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut client_seed);
+        let color = match connection
+            .request(Command::JoinGame {
+                id: game_id,
+                color,
+                client_seed: Some(hex::encode(client_seed)),
+            })
+            .await?
+        {
+            Response::PickedColor {
+                color,
+                rejoin_token,
+            } => {
+                *connection.seat.borrow_mut() = Some(Seat {
+                    game_id,
+                    color: color.clone(),
+                    rejoin_token,
+                });
+                color
+            }
+            other => return Err(anyhow!(format!("Unexpected reply {:?}", other))),
+        };
+        Ok(Session {
+            connection,
+            game_id,
+            color,
+        })
+    }
+
+    pub async fn start(&self) -> Result<()> {
+        self.connection
+            .request(Command::StartGame { id: self.game_id })
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn roll_dice(&self) -> Result<()> {
+        self.connection
+            .request(Command::RollDice { id: self.game_id })
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn move_token(&self, token_id: u8) -> Result<()> {
+        self.connection
+            .request(Command::MoveToken {
+                id: self.game_id,
+                token_id,
+            })
+            .await
+            .map(|_| ())
+    }
+}
+
+/// Encoding of messages after hello, `?encoding=messagepack` asks for binary frames instead of json.
+pub fn wire_encoding() -> Result<Encoding> {
+    match query_param("encoding")?.as_deref() {
+        None | Some("json") => Ok(Encoding::Json),
+        Some("messagepack") => Ok(Encoding::MessagePack),
+        Some(other) => Err(anyhow!("Unknown encoding {}", other)),
+    }
+}
+
+/// Game server address, `?server=ws://host:port/game` overrides default of same host on port 3000.
+pub fn server_url() -> Result<String> {
+    if let Some(url) = query_param("server")? {
+        return Ok(url);
+    }
+    let hostname = window()?
+        .location()
+        .hostname()
+        .map_err(|err| anyhow!(format!("Failed to get hostname {:#?}", err)))?;
+    Ok(format!("ws://{}:3000/game", hostname))
+}
@@ -0,0 +1,211 @@
+use std::cell::{Cell, RefCell};
+
+use anyhow::{anyhow, bail, Result};
+
+use crate::server::entity::{
+    action::GameEvent,
+    bot::{self, Difficulty},
+    color::Color,
+    game::Game,
+};
+
+/// Game run by same rules engine as server, in this browser.
+/// Players either pass device around, or play against computer.
+pub struct OfflineGame {
+    game: RefCell<Game>,
+    bots: Option<Bots>,
+    on_event: Box<dyn Fn(GameEvent)>,
+}
+
+struct Bots {
+    colors: Vec<Color>,
+    difficulty: Difficulty,
+    // milliseconds a bot waits before each roll and move, so that players can follow it.
+    delay: f64,
+    // when bot whose turn it is acts next.
+    ready_at: Cell<Option<f64>>,
+}
+
+impl OfflineGame {
+    /// Seats first `players` colors in turn order and starts right away.
+    pub fn start(players: usize, on_event: impl Fn(GameEvent) + 'static) -> Result<Self> {
+        OfflineGame::new(players, None, on_event)
+    }
+
+    /// Player is yellow, and bots take colors after it.
+    pub fn against_bots(
+        bots: usize,
+        difficulty: Difficulty,
+        delay: f64,
+        on_event: impl Fn(GameEvent) + 'static,
+    ) -> Result<Self> {
+        let colors = Color::ALL
+            .get(1..=bots)
+            .ok_or_else(|| anyhow!("There can be at most {} bots", Color::ALL.len() - 1))?;
+        let bots = Bots {
+            colors: colors.to_vec(),
+            difficulty,
+            delay,
+            ready_at: Cell::new(None),
+        };
+        OfflineGame::new(colors.len() + 1, Some(bots), on_event)
+    }
+
+    fn new(
+        players: usize,
+        bots: Option<Bots>,
+        on_event: impl Fn(GameEvent) + 'static,
+    ) -> Result<Self> {
+        let colors = Color::ALL
+            .get(..players)
+            .ok_or_else(|| anyhow!("Ludo is played by at most {} players", Color::ALL.len()))?;
+        let offline = OfflineGame {
+            game: RefCell::new(Game::new(0)),
+            bots,
+            on_event: Box::new(on_event),
+        };
+        offline.update(|game| {
+            let mut events = Vec::new();
+            for color in colors {
+                events.extend(game.join(color.clone())?.1);
+            }
+            events.extend(game.start()?);
+            Ok(events)
+        })?;
+        Ok(offline)
+    }
+
+    pub fn roll_dice(&self) -> Result<()> {
+        self.players_turn()?;
+        self.roll()
+    }
+
+    pub fn move_token(&self, token_id: u8) -> Result<()> {
+        self.players_turn()?;
+        self.move_token_of_current(token_id)
+    }
+
+    /// Lets bot whose turn it is act, once it has waited long enough and board is not busy animating.
+    /// now is time in milliseconds. Returns true while a bot is yet to act.
+    pub fn tick_bots(&self, now: f64, idle: bool) -> Result<bool> {
+        let bots = match &self.bots {
+            Some(bots) if self.is_bot_turn() => bots,
+            _ => return Ok(false),
+        };
+        if !idle {
+            return Ok(true);
+        }
+        match bots.ready_at.get() {
+            None => bots.ready_at.set(Some(now + bots.delay)),
+            Some(ready_at) if now < ready_at => (),
+            Some(_) => {
+                bots.ready_at.set(None);
+                self.play_bot(bots.difficulty)?;
+            }
+        }
+        Ok(true)
+    }
+
+    fn play_bot(&self, difficulty: Difficulty) -> Result<()> {
+        let token_id = {
+            let game = self.game.borrow();
+            match (game.pending_dice(), game.current_turn()) {
+                (Some(dice), Some(color)) => {
+                    let tokens = game
+                        .tokens(color)
+                        .ok_or_else(|| anyhow!("{:?} is not playing", color))?;
+                    bot::choose_token(tokens, dice, difficulty)
+                }
+                _ => None,
+            }
+        };
+        match token_id {
+            Some(token_id) => self.move_token_of_current(token_id),
+            None => self.roll(),
+        }
+    }
+
+    fn is_bot_turn(&self) -> bool {
+        match (&self.bots, self.game.borrow().current_turn()) {
+            (Some(bots), Some(color)) => bots.colors.contains(color),
+            _ => false,
+        }
+    }
+
+    fn players_turn(&self) -> Result<()> {
+        if self.is_bot_turn() {
+            bail!("Wait for bot to play");
+        }
+        Ok(())
+    }
+
+    fn roll(&self) -> Result<()> {
+        self.update(|game| {
+            let color = game.current_turn().cloned();
+            let color = color.ok_or_else(|| anyhow!("Game is not in progress"))?;
+            let dice = game.next_dice();
+            game.roll(&color, dice)
+        })
+    }
+
+    fn move_token_of_current(&self, token_id: u8) -> Result<()> {
+        self.update(|game| {
+            let color = game.current_turn().cloned();
+            let color = color.ok_or_else(|| anyhow!("Game is not in progress"))?;
+            game.move_token(&color, token_id)
+        })
+    }
+
+    // events are handed out only after game is no longer borrowed.
+    fn update(&self, update: impl FnOnce(&mut Game) -> Result<Vec<GameEvent>>) -> Result<()> {
+        let events = update(&mut self.game.borrow_mut())?;
+        for event in events {
+            (self.on_event)(event);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::rc::Rc;
+
+    use super::*;
+
+    #[test]
+    fn test_hot_seat_game_starts_with_first_colors() {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let heard = events.clone();
+        let game = OfflineGame::start(3, move |event| heard.borrow_mut().push(event)).unwrap();
+        assert!(events.borrow().contains(&GameEvent::GameStarted {
+            players: vec![Color::Yellow, Color::Blue, Color::Red],
+        }));
+        assert!(game.move_token(1).is_err());
+        assert!(game.roll_dice().is_ok());
+        assert!(OfflineGame::start(5, |_| ()).is_err());
+    }
+
+    #[test]
+    fn test_bots_play_their_turns() {
+        let game = OfflineGame::against_bots(3, Difficulty::Hard, 100., |_| ()).unwrap();
+        // yellow passes its turn on to bots.
+        while game.game.borrow().current_turn() == Some(&Color::Yellow) {
+            let pending = game.game.borrow().pending_dice();
+            match pending {
+                Some(_) => game.move_token(1).unwrap(),
+                None => game.roll_dice().unwrap(),
+            }
+        }
+        assert!(game.roll_dice().is_err());
+        assert!(game.tick_bots(0., false).unwrap());
+        assert!(game.tick_bots(0., true).unwrap());
+        assert!(game.tick_bots(50., true).unwrap());
+        assert_eq!(game.game.borrow().pending_dice(), None);
+        let mut now = 0.;
+        while game.is_bot_turn() {
+            now += 100.;
+            game.tick_bots(now, true).unwrap();
+        }
+        assert_eq!(game.game.borrow().current_turn(), Some(&Color::Yellow));
+    }
+}
@@ -0,0 +1,30 @@
+use std::rc::Rc;
+
+use anyhow::Result;
+
+use crate::network::Session;
+use crate::offline::OfflineGame;
+
+/// Where moves of local players go, game server when online or rules engine in browser when offline.
+/// Either way, outcome comes back as game events.
+#[derive(Clone)]
+pub enum Play {
+    Online(Session),
+    Offline(Rc<OfflineGame>),
+}
+
+impl Play {
+    pub async fn roll_dice(&self) -> Result<()> {
+        match self {
+            Play::Online(session) => session.roll_dice().await,
+            Play::Offline(game) => game.roll_dice(),
+        }
+    }
+
+    pub async fn move_token(&self, token_id: u8) -> Result<()> {
+        match self {
+            Play::Online(session) => session.move_token(token_id).await,
+            Play::Offline(game) => game.move_token(token_id),
+        }
+    }
+}
@@ -1,8 +1,366 @@
+use super::color::Color;
+use super::coordinate::Coordinate;
+use super::position::{AntiClockNeighbor, Position};
+use super::theme::Theme;
+
 #[derive(Debug, Clone)]
 pub struct BoardConfiguration {
     pub vertices: Vec<f32>,
+    pub normals: Vec<f32>,
     pub indices: Vec<u16>,
     pub colors: Vec<f32>,
-    pub start_index_dice: i32,
-    pub end_index_dice: i32,
+}
+
+impl BoardConfiguration {
+    /// Cornor of board for each color, starting from left near and going anti clock wise.
+    pub fn position_color_map(left_near_color: &Color) -> [(Position, Color); 4] {
+        [
+            (Position::LeftNear, left_near_color.clone()),
+            (Position::LeftFar, left_near_color.neighbor().clone()),
+            (
+                Position::RightFar,
+                left_near_color.neighbor().neighbor().clone(),
+            ),
+            (
+                Position::RightNear,
+                left_near_color.neighbor().neighbor().neighbor().clone(),
+            ),
+        ]
+    }
+
+    pub fn new(coorinate: &Coordinate, left_near_color: &Color, theme: &Theme) -> Self {
+        let mut outer_board: Vec<f32> = Vec::new();
+        Self::extend_with_cube_vertices(coorinate.clone(), &mut outer_board);
+
+        let mut colors: Vec<f32> = Vec::new();
+        for face_color in [
+            theme.yellow, // v0-v1-v2-v3 front
+            theme.green,  // v0-v3-v4-v5 right
+            theme.lines,  // v0-v5-v6-v1 up, seen b/w tiles
+            theme.blue,   // v1-v6-v7-v2 left
+            theme.lines,  // v7-v4-v3-v2 down
+            theme.red,    // v4-v7-v6-v5 back
+        ] {
+            for _ in 0..4 {
+                colors.extend_from_slice(&face_color);
+            }
+        }
+
+        let mut indices = vec![
+            0, 1, 2, 0, 2, 3, // front
+            4, 5, 6, 4, 6, 7, // right
+            8, 9, 10, 8, 10, 11, // up
+            12, 13, 14, 12, 14, 15, // left
+            16, 17, 18, 16, 18, 19, // down
+            20, 21, 22, 20, 22, 23,
+        ];
+
+        let Coordinate {
+            right,
+            left,
+            near,
+            far,
+            top,
+            ..
+        } = coorinate;
+        let widht = f32::abs(right - left);
+        let depth = f32::abs(far - near);
+
+        let inner_sq_left = left + 6. * widht / 15.;
+        let inner_sq_right = left + 9. * widht / 15.;
+        let inner_sq_near = near - 6. * depth / 15.;
+        let inner_sq_far = near - 9. * depth / 15.;
+        let center_x = left + widht / 2.;
+        let center_z = near - depth / 2.;
+
+        let v8 = [inner_sq_right, top + 0.15, inner_sq_near];
+        let v9 = [inner_sq_left, top + 0.15, inner_sq_near];
+        let v10 = [inner_sq_left, top + 0.15, inner_sq_far];
+        let v11 = [inner_sq_right, top + 0.15, inner_sq_far];
+        let vmid = [center_x, top + 0.15, center_z];
+        // add inner block in anti clock orientation.
+        outer_board.extend_from_slice(&v8);
+        outer_board.extend_from_slice(&v9);
+        outer_board.extend_from_slice(&vmid);
+        outer_board.extend_from_slice(&v10);
+        outer_board.extend_from_slice(&vmid);
+        outer_board.extend_from_slice(&v9);
+        outer_board.extend_from_slice(&v11);
+        outer_board.extend_from_slice(&vmid);
+        outer_board.extend_from_slice(&v10);
+        outer_board.extend_from_slice(&v8);
+        outer_board.extend_from_slice(&vmid);
+        outer_board.extend_from_slice(&v11);
+
+        indices.extend_from_slice(&[24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35]);
+
+        let position_color_map = Self::position_color_map(left_near_color);
+
+        for position_color in &position_color_map {
+            let color = theme.color(&position_color.1);
+            for _ in 0..3 {
+                colors.extend_from_slice(&color);
+            }
+        }
+
+        // Now lets make cornor sq first, coloured as well as inner white.
+        for position_color in &position_color_map {
+            for i in 0..2 {
+                let factor = 1. / (i as f32 + 1.);
+                let color_tuple = if factor == 1. {
+                    theme.color(&position_color.1)
+                } else {
+                    theme.board
+                };
+
+                Self::extend_cornor(
+                    coorinate,
+                    &mut colors,
+                    &mut outer_board,
+                    &mut indices,
+                    &position_color.0,
+                    color_tuple,
+                    factor,
+                );
+            }
+        }
+
+        for position_color in &position_color_map {
+            Self::extend_for_all_color_conor_sq_inner_block(
+                coorinate,
+                &mut colors,
+                &mut outer_board,
+                &mut indices,
+                &position_color.0,
+                theme.color(&position_color.1),
+            );
+        }
+
+        for position_color in &position_color_map {
+            Self::extend_for_color_tile(
+                coorinate,
+                &mut colors,
+                &mut outer_board,
+                &mut indices,
+                &position_color.0,
+                &position_color.1,
+                theme,
+            );
+        }
+
+        let mut normals = Vec::<f32>::new();
+        Self::extend_with_cube_normals(&mut normals);
+        // everything else on board lies flat on top of it.
+        while normals.len() < outer_board.len() {
+            normals.extend_from_slice(&[0., 1., 0.]);
+        }
+
+        BoardConfiguration {
+            vertices: outer_board,
+            normals,
+            colors,
+            indices,
+        }
+    }
+
+    fn extend_with_cube_vertices(coorinate: Coordinate, vertices: &mut Vec<f32>) {
+        let Coordinate {
+            right,
+            left,
+            near,
+            far,
+            top,
+            bottom,
+        } = coorinate;
+        let vo = [right, top, near];
+        let v1 = [left, top, near];
+        let v2 = [left, bottom, near];
+        let v3 = [right, bottom, near];
+        let v4 = [right, bottom, far];
+        let v5 = [right, top, far];
+        let v6 = [left, top, far];
+        let v7 = [left, bottom, far];
+
+        // Create a ludo board
+        //    v6----- v5
+        //   /|      /|
+        //  v1------v0|
+        //  | |     | |
+        //  | |v7---|-|v4
+        //  |/      |/
+        //  v2------v3
+        // v0-v1-v2-v3 front
+        vertices.extend_from_slice(&vo);
+        vertices.extend_from_slice(&v1);
+        vertices.extend_from_slice(&v2);
+        vertices.extend_from_slice(&v3);
+        // v0-v3-v4-v5 right
+        vertices.extend_from_slice(&vo);
+        vertices.extend_from_slice(&v3);
+        vertices.extend_from_slice(&v4);
+        vertices.extend_from_slice(&v5);
+        // v0-v5-v6-v1 up
+        vertices.extend_from_slice(&vo);
+        vertices.extend_from_slice(&v5);
+        vertices.extend_from_slice(&v6);
+        vertices.extend_from_slice(&v1);
+        // v1-v6-v7-v2 left
+        vertices.extend_from_slice(&v1);
+        vertices.extend_from_slice(&v6);
+        vertices.extend_from_slice(&v7);
+        vertices.extend_from_slice(&v2);
+        // v7-v4-v3-v2 down
+        vertices.extend_from_slice(&v7);
+        vertices.extend_from_slice(&v4);
+        vertices.extend_from_slice(&v3);
+        vertices.extend_from_slice(&v2);
+        // v4-v7-v6-v5 back
+        vertices.extend_from_slice(&v4);
+        vertices.extend_from_slice(&v7);
+        vertices.extend_from_slice(&v6);
+        vertices.extend_from_slice(&v5);
+    }
+
+    // normals in same face order as extend_with_cube_vertices.
+    fn extend_with_cube_normals(normals: &mut Vec<f32>) {
+        for normal in [
+            [0., 0., 1.],  // front
+            [1., 0., 0.],  // right
+            [0., 1., 0.],  // up
+            [-1., 0., 0.], // left
+            [0., -1., 0.], // down
+            [0., 0., -1.], // back
+        ] {
+            for _ in 0..4 {
+                normals.extend_from_slice(&normal);
+            }
+        }
+    }
+    fn extend_for_color_tile(
+        coorinate: &Coordinate,
+        colors: &mut Vec<f32>,
+        vertices: &mut Vec<f32>,
+        indices: &mut Vec<u16>,
+        position: &Position,
+        color: &Color,
+        theme: &Theme,
+    ) {
+        let tile_vertex = position.play_tile_vertices(coorinate);
+        let mut tile_vertex = tile_vertex.chunks(3);
+        let has_color = position.play_tile_has_color();
+        let color_tuple = theme.color(color);
+        let white_tuple = theme.board;
+        for i in 0..18 {
+            let one = tile_vertex.next().unwrap();
+            let two = tile_vertex.next().unwrap();
+            let three = tile_vertex.next().unwrap();
+            let four = tile_vertex.next().unwrap();
+
+            let index_begin = vertices.len() as u16 / 3;
+            vertices.extend_from_slice(one);
+            vertices.extend_from_slice(two);
+            vertices.extend_from_slice(three);
+            vertices.extend_from_slice(four);
+
+            let selected_color_tuple = if has_color[i] {
+                color_tuple
+            } else {
+                white_tuple
+            };
+            for _ in 0..4 {
+                colors.extend_from_slice(&selected_color_tuple);
+            }
+
+            indices.extend_from_slice(&[
+                index_begin,
+                index_begin + 1,
+                index_begin + 2,
+                index_begin,
+                index_begin + 2,
+                index_begin + 3,
+            ]);
+        }
+    }
+
+    fn extend_for_all_color_conor_sq_inner_block(
+        coorinate: &Coordinate,
+        colors: &mut Vec<f32>,
+        vertices: &mut Vec<f32>,
+        indices: &mut Vec<u16>,
+        position: &Position,
+        color_tuple: [f32; 3],
+    ) {
+        let four_sq_matrix = position.conor_sq_inner_block(coorinate);
+        let mut four_sq_matrix = four_sq_matrix.chunks(3);
+        for _ in 0..4 {
+            let one = four_sq_matrix.next().unwrap();
+            let two = four_sq_matrix.next().unwrap();
+            let three = four_sq_matrix.next().unwrap();
+            let four = four_sq_matrix.next().unwrap();
+
+            let index_begin = vertices.len() as u16 / 3;
+            vertices.extend_from_slice(one);
+            vertices.extend_from_slice(two);
+            vertices.extend_from_slice(three);
+            vertices.extend_from_slice(four);
+
+            for _ in 0..4 {
+                colors.extend_from_slice(&color_tuple);
+            }
+
+            indices.extend_from_slice(&[
+                index_begin,
+                index_begin + 1,
+                index_begin + 2,
+                index_begin,
+                index_begin + 2,
+                index_begin + 3,
+            ]);
+        }
+    }
+
+    fn extend_cornor(
+        coorinate: &Coordinate,
+        colors: &mut Vec<f32>,
+        vertices: &mut Vec<f32>,
+        indices: &mut Vec<u16>,
+        position: &Position,
+        color_tuple: [f32; 3],
+        factor: f32,
+    ) {
+        let begin_index = vertices.len() as u16 / 3;
+        let cornor_sq_vertices = position.cornor_sq_vetices(coorinate, factor);
+        vertices.extend_from_slice(&cornor_sq_vertices);
+        for _ in 0..16 {
+            colors.extend_from_slice(&color_tuple);
+        }
+
+        for i in 0..4 {
+            let begin_index = (i * 4) + begin_index;
+            indices.extend_from_slice(&[
+                begin_index,
+                begin_index + 1,
+                begin_index + 2,
+                begin_index,
+                begin_index + 2,
+                begin_index + 3,
+            ]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_every_vertex_has_unit_normal() {
+        let board =
+            BoardConfiguration::new(&Coordinate::for_board(), &Color::Yellow, &Theme::default());
+        assert_eq!(board.vertices.len(), board.normals.len());
+        for normal in board.normals.chunks(3) {
+            let length = normal.iter().map(|n| n * n).sum::<f32>().sqrt();
+            assert!((length - 1.).abs() < 1e-6);
+        }
+    }
 }
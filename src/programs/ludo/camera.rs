@@ -0,0 +1,206 @@
+use std::f32::consts::PI;
+
+use nalgebra::{Matrix4, Point3, Vector3};
+
+use super::color::Color;
+
+const FIELD_OF_VIEW: f32 = PI / 4.;
+const Z_NEAR: f32 = 1.;
+const Z_FAR: f32 = 100.;
+
+const MIN_PITCH: f32 = PI * 10. / 180.;
+const MAX_PITCH: f32 = PI * 85. / 180.;
+const MIN_DISTANCE: f32 = 18.;
+const MAX_DISTANCE: f32 = 60.;
+
+// radians turned for every pixel dragged.
+const ORBIT_SPEED: f32 = 0.008;
+// fraction of remaining yaw covered on every update while turning towards a player.
+const TRANSITION_SPEED: f32 = 0.08;
+const TRANSITION_DONE: f32 = 0.001;
+
+/// Orbit camera looking at the center of board.
+/// Yaw 0 puts camera on positive z side of board, looking towards far edge.
+#[derive(Debug, Clone)]
+pub struct Camera {
+    target: Point3<f32>,
+    yaw: f32,
+    pitch: f32,
+    distance: f32,
+    aspect: f32,
+    target_yaw: Option<f32>,
+}
+
+impl Camera {
+    pub fn new(aspect: f32) -> Self {
+        Camera {
+            target: Point3::new(0., 5., -20.),
+            yaw: 0.,
+            pitch: PI * 35. / 180.,
+            distance: 36.,
+            aspect,
+            target_yaw: None,
+        }
+    }
+
+    /// Yaw which brings home of given color nearest to viewer.
+    /// Yellow home is left near cornor of board and others follow anti clock wise.
+    pub fn yaw_for(color: &Color) -> f32 {
+        let quadrant = match color {
+            Color::Yellow => 0.,
+            Color::Blue => 1.,
+            Color::Red => 2.,
+            Color::Green => 3.,
+        };
+        normalize_angle(-PI / 4. - quadrant * PI / 2.)
+    }
+
+    /// Width over height of canvas, changes whenever window is resized or rotated.
+    pub fn set_aspect(&mut self, aspect: f32) {
+        if aspect.is_finite() && aspect > 0. {
+            self.aspect = aspect;
+        }
+    }
+
+    /// Starts smooth turn of board, so that given color is nearest.
+    pub fn look_from(&mut self, color: &Color) {
+        self.target_yaw = Some(Camera::yaw_for(color));
+    }
+
+    /// dx and dy are in pixels. User dragging cancels any ongoing transition.
+    pub fn orbit(&mut self, dx: f32, dy: f32) {
+        self.target_yaw = None;
+        self.yaw = normalize_angle(self.yaw - dx * ORBIT_SPEED);
+        self.pitch = (self.pitch + dy * ORBIT_SPEED).clamp(MIN_PITCH, MAX_PITCH);
+    }
+
+    /// factor > 1 moves camera away and factor < 1 brings it closer.
+    pub fn zoom(&mut self, factor: f32) {
+        if factor.is_finite() && factor > 0. {
+            self.distance = (self.distance * factor).clamp(MIN_DISTANCE, MAX_DISTANCE);
+        }
+    }
+
+    pub fn is_animating(&self) -> bool {
+        self.target_yaw.is_some()
+    }
+
+    /// Moves one step towards target yaw. Returns true if camera has moved.
+    pub fn update(&mut self) -> bool {
+        let target_yaw = match self.target_yaw {
+            Some(target_yaw) => target_yaw,
+            None => return false,
+        };
+        let remaining = normalize_angle(target_yaw - self.yaw);
+        if remaining.abs() < TRANSITION_DONE {
+            self.yaw = target_yaw;
+            self.target_yaw = None;
+        } else {
+            self.yaw = normalize_angle(self.yaw + remaining * TRANSITION_SPEED);
+        }
+        true
+    }
+
+    pub fn eye(&self) -> Point3<f32> {
+        let offset = Vector3::new(
+            self.pitch.cos() * self.yaw.sin(),
+            self.pitch.sin(),
+            self.pitch.cos() * self.yaw.cos(),
+        );
+        self.target + offset * self.distance
+    }
+
+    pub fn view_matrix(&self) -> Matrix4<f32> {
+        Matrix4::look_at_rh(&self.eye(), &self.target, &Vector3::y())
+    }
+
+    pub fn projection_matrix(&self) -> Matrix4<f32> {
+        Matrix4::new_perspective(self.aspect, FIELD_OF_VIEW, Z_NEAR, Z_FAR)
+    }
+
+    pub fn view_projection(&self) -> Matrix4<f32> {
+        self.projection_matrix() * self.view_matrix()
+    }
+}
+
+// keeps angle in (-PI, PI], so that transitions take shortest way around.
+fn normalize_angle(angle: f32) -> f32 {
+    let angle = angle.rem_euclid(2. * PI);
+    if angle > PI {
+        angle - 2. * PI
+    } else {
+        angle
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_pitch_and_zoom_are_clamped() {
+        let mut camera = Camera::new(1.);
+        camera.orbit(0., 10_000.);
+        assert_eq!(camera.pitch, MAX_PITCH);
+        camera.orbit(0., -10_000.);
+        assert_eq!(camera.pitch, MIN_PITCH);
+
+        camera.zoom(100.);
+        assert_eq!(camera.distance, MAX_DISTANCE);
+        camera.zoom(0.01);
+        assert_eq!(camera.distance, MIN_DISTANCE);
+    }
+
+    #[test]
+    fn test_own_color_is_nearest() {
+        let mut camera = Camera::new(1.);
+        // board cornor of each color, in anti clock wise order starting with yellow.
+        let homes = [
+            (Color::Yellow, Point3::new(-10., 5., -10.)),
+            (Color::Blue, Point3::new(-10., 5., -30.)),
+            (Color::Red, Point3::new(10., 5., -30.)),
+            (Color::Green, Point3::new(10., 5., -10.)),
+        ];
+        for (color, home) in &homes {
+            camera.yaw = Camera::yaw_for(color);
+            let eye = camera.eye();
+            let nearest = homes
+                .iter()
+                .min_by(|a, b| {
+                    let a = (a.1 - eye).norm();
+                    let b = (b.1 - eye).norm();
+                    a.partial_cmp(&b).unwrap()
+                })
+                .unwrap();
+            assert_eq!((nearest.1 - home).norm(), 0.);
+        }
+    }
+
+    #[test]
+    fn test_transition_reaches_target_the_short_way() {
+        let mut camera = Camera::new(1.);
+        camera.yaw = Camera::yaw_for(&Color::Yellow);
+        camera.look_from(&Color::Green);
+        let target = Camera::yaw_for(&Color::Green);
+
+        let mut previous = camera.yaw;
+        let mut travelled = 0.;
+        while camera.update() {
+            travelled += normalize_angle(camera.yaw - previous).abs();
+            previous = camera.yaw;
+        }
+        // yellow and green are neighbors, so we should not travel more than quarter turn.
+        assert!(travelled <= PI / 2. + 0.01);
+        assert!(!camera.is_animating());
+        assert_eq!(camera.yaw, target);
+    }
+
+    #[test]
+    fn test_orbit_cancels_transition() {
+        let mut camera = Camera::new(1.);
+        camera.look_from(&Color::Red);
+        camera.orbit(5., 0.);
+        assert!(!camera.is_animating());
+        assert!(!camera.update());
+    }
+}
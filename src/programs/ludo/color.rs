@@ -21,13 +21,14 @@ impl AntiClockNeighbor for Color {
     }
 }
 
-impl Color {
-    pub fn get_color_tuple(&self) -> [f32; 3] {
-        match self {
-            Color::Red => [1., 0., 0.],
-            Color::Green => [0., 1., 0.],
-            Color::Yellow => [1., 1., 0.],
-            Color::Blue => [0., 0., 1.],
+impl From<&crate::server::entity::color::Color> for Color {
+    fn from(color: &crate::server::entity::color::Color) -> Self {
+        use crate::server::entity::color::Color as ServerColor;
+        match color {
+            ServerColor::Yellow => Color::Yellow,
+            ServerColor::Blue => Color::Blue,
+            ServerColor::Red => Color::Red,
+            ServerColor::Green => Color::Green,
         }
     }
 }
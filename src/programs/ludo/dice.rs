@@ -0,0 +1,151 @@
+use std::f32::consts::PI;
+
+use nalgebra::Vector3;
+
+use crate::engine::mesh::Mesh;
+
+use super::theme::Theme;
+
+// dice is a cube of this size, centered on origin.
+const SIZE: f32 = 2.;
+const PIP_RADIUS: f32 = 0.18;
+const PIP_SEGMENTS: u16 = 12;
+// pips are lifted a bit above face, so that they do not fight with it for depth.
+const PIP_LIFT: f32 = 0.005;
+
+// value of a face along with its outward normal and two axes (u, v) lying on it, u x v = normal.
+type Face = (u8, [f32; 3], [f32; 3], [f32; 3]);
+
+// opposite faces add up to seven.
+const FACES: [Face; 6] = [
+    (1, [0., 1., 0.], [0., 0., 1.], [1., 0., 0.]),
+    (6, [0., -1., 0.], [1., 0., 0.], [0., 0., 1.]),
+    (2, [0., 0., 1.], [1., 0., 0.], [0., 1., 0.]),
+    (5, [0., 0., -1.], [0., 1., 0.], [1., 0., 0.]),
+    (3, [1., 0., 0.], [0., 1., 0.], [0., 0., 1.]),
+    (4, [-1., 0., 0.], [0., 0., 1.], [0., 1., 0.]),
+];
+
+/// Dice with pips standing on its center, one face showing each value.
+/// Every face, pips included, maps onto whole of texture.
+pub(super) fn mesh(theme: &Theme) -> Mesh {
+    let mut mesh = Mesh::default();
+    let half = SIZE / 2.;
+    for (value, normal, u, v) in FACES.iter() {
+        let normal = Vector3::from(*normal);
+        let u = Vector3::from(*u);
+        let v = Vector3::from(*v);
+
+        let center = normal * half;
+        let begin = mesh.vertex_count() as u16;
+        for (a, b) in [(-1., -1.), (1., -1.), (1., 1.), (-1., 1.)] {
+            push_vertex(
+                &mut mesh,
+                center + (u * a + v * b) * half,
+                (normal, u, v),
+                theme.dice,
+            );
+        }
+        mesh.indices
+            .extend_from_slice(&[begin, begin + 1, begin + 2, begin, begin + 2, begin + 3]);
+
+        for (a, b) in pip_layout(*value) {
+            let pip_center = center + normal * PIP_LIFT + (u * *a + v * *b) * half / 2.;
+            push_pip(&mut mesh, pip_center, normal, u, v, theme.pips);
+        }
+    }
+    mesh
+}
+
+// where pips sit on a face, in halves of distance from face center to its edge.
+fn pip_layout(value: u8) -> &'static [(f32, f32)] {
+    match value {
+        1 => &[(0., 0.)],
+        2 => &[(-1., -1.), (1., 1.)],
+        3 => &[(-1., -1.), (0., 0.), (1., 1.)],
+        4 => &[(-1., -1.), (1., -1.), (1., 1.), (-1., 1.)],
+        5 => &[(-1., -1.), (1., -1.), (0., 0.), (1., 1.), (-1., 1.)],
+        _ => &[
+            (-1., -1.),
+            (-1., 0.),
+            (-1., 1.),
+            (1., -1.),
+            (1., 0.),
+            (1., 1.),
+        ],
+    }
+}
+
+// flat disc made of a fan of triangles around its center.
+fn push_pip(
+    mesh: &mut Mesh,
+    center: Vector3<f32>,
+    normal: Vector3<f32>,
+    u: Vector3<f32>,
+    v: Vector3<f32>,
+    color: [f32; 3],
+) {
+    let begin = mesh.vertex_count() as u16;
+    push_vertex(mesh, center, (normal, u, v), color);
+    for i in 0..PIP_SEGMENTS {
+        let angle = 2. * PI * i as f32 / PIP_SEGMENTS as f32;
+        let (sin, cos) = angle.sin_cos();
+        push_vertex(
+            mesh,
+            center + (u * cos + v * sin) * PIP_RADIUS,
+            (normal, u, v),
+            color,
+        );
+    }
+    for i in 0..PIP_SEGMENTS {
+        let next = (i + 1) % PIP_SEGMENTS;
+        mesh.indices
+            .extend_from_slice(&[begin, begin + 1 + i, begin + 1 + next]);
+    }
+}
+
+// texture coordinate is where vertex lies along u and v of its face.
+fn push_vertex(
+    mesh: &mut Mesh,
+    position: Vector3<f32>,
+    (normal, u, v): (Vector3<f32>, Vector3<f32>, Vector3<f32>),
+    color: [f32; 3],
+) {
+    let half = SIZE / 2.;
+    mesh.vertices.extend_from_slice(position.as_slice());
+    mesh.normals.extend_from_slice(normal.as_slice());
+    mesh.colors.extend_from_slice(&color);
+    mesh.uv.extend_from_slice(&[
+        (position.dot(&u) / half + 1.) / 2.,
+        (position.dot(&v) / half + 1.) / 2.,
+    ]);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_every_face_shows_its_value() {
+        let theme = Theme::default();
+        let mesh = mesh(&theme);
+        assert!(mesh.validate().is_ok());
+        assert!(!mesh.uv.is_empty());
+        assert!(mesh.uv.iter().all(|uv| (0. ..=1.).contains(uv)));
+        for (value, normal, _, _) in FACES.iter() {
+            let pip_vertices = mesh
+                .normals
+                .chunks(3)
+                .zip(mesh.colors.chunks(3))
+                .filter(|(n, color)| n == normal && *color == theme.pips)
+                .count();
+            assert_eq!(pip_vertices, *value as usize * (PIP_SEGMENTS as usize + 1));
+
+            let opposite = FACES
+                .iter()
+                .find(|(_, other, _, _)| other.iter().zip(normal).all(|(a, b)| *a == -b))
+                .unwrap();
+            assert_eq!(value + opposite.0, 7);
+        }
+    }
+}
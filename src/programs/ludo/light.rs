@@ -0,0 +1,62 @@
+use nalgebra::{Matrix4, Point3, Vector3};
+
+// light looks at center of board from this far, and sees this much around center.
+const LIGHT_DISTANCE: f32 = 30.;
+const LIGHT_EXTENT: f32 = 16.;
+
+/// Sun like directional light shining over board, plus some ambient light so that
+/// faces turned away from sun are not completely black.
+#[derive(Debug, Clone)]
+pub(super) struct Light {
+    // direction towards the light, not from it.
+    pub direction: Vector3<f32>,
+    pub color: [f32; 3],
+    pub ambient: [f32; 3],
+}
+
+impl Default for Light {
+    fn default() -> Self {
+        Light {
+            direction: Vector3::new(0.4, 3., 1.5).normalize(),
+            color: [0.8, 0.8, 0.8],
+            ambient: [0.3, 0.3, 0.3],
+        }
+    }
+}
+
+impl Light {
+    /// Orthographic view of board as seen from light, used to render shadow map.
+    /// Sun is far away, so its rays are treated as parallel.
+    pub fn view_projection(&self, center: &Point3<f32>) -> Matrix4<f32> {
+        let eye = center + self.direction * LIGHT_DISTANCE;
+        let view = Matrix4::look_at_rh(&eye, center, &Vector3::y());
+        let projection = Matrix4::new_orthographic(
+            -LIGHT_EXTENT,
+            LIGHT_EXTENT,
+            -LIGHT_EXTENT,
+            LIGHT_EXTENT,
+            1.,
+            2. * LIGHT_DISTANCE,
+        );
+        projection * view
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_whole_board_fits_in_light_view() {
+        let light = Light::default();
+        let view_projection = light.view_projection(&Point3::new(0., 5., -20.));
+        for x in [-10., 10.] {
+            for z in [-10., -30.] {
+                for y in [5., 8.] {
+                    let clip = view_projection.transform_point(&Point3::new(x, y, z));
+                    assert!(clip.coords.iter().all(|c| c.abs() < 1.), "{:?}", clip);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,366 @@
+use anyhow::{anyhow, Result};
+
+use crate::server::entity::{
+    action::{GameEvent, LegalMove},
+    color::Color,
+    fair_dice,
+    token::{Status, Token},
+};
+
+// dice turns this many degree a second, and stops after a full spin.
+const DICE_SPEED: f32 = 30.;
+const DICE_SPIN: f32 = 45.;
+// part of a move done in a second, a token takes 1 / TOKEN_SPEED seconds to reach its new spot.
+const TOKEN_SPEED: f32 = 3.;
+
+/// What screen is waiting on.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Phase {
+    /// Nothing to show until some player acts.
+    Waiting,
+    /// Dice is spinning, angle is in degree.
+    Rolling { angle: f32 },
+    /// Local player rolled and has to pick a token to move.
+    ChoosingToken { dice: u8 },
+    /// Token is walking from `from`, progress goes from 0 to 1.
+    Animating {
+        color: Color,
+        token_id: u8,
+        from: Status,
+        progress: f32,
+    },
+}
+
+impl Phase {
+    /// Rotation of dice around center of board, in degree.
+    pub fn dice_angle(&self) -> f32 {
+        match self {
+            Phase::Rolling { angle } => *angle,
+            _ => 0.,
+        }
+    }
+}
+
+/// Game as seen by this browser. Server events change it, renderer only reads it.
+#[derive(Debug)]
+pub struct LudoState {
+    // colors played from this browser, one when online and all of them in hot-seat play.
+    local: Vec<Color>,
+    players: Vec<Color>,
+    tokens: Vec<Token>,
+    turn: Option<Color>,
+    last_roll: Option<(Color, u8)>,
+    // moves server allows with last roll, until one of them is made.
+    legal_moves: Vec<LegalMove>,
+    // what server committed dice to, and every roll since, to check once its seed is revealed.
+    seed_hash: Option<String>,
+    client_seeds: Vec<String>,
+    rolls: Vec<u8>,
+    started: bool,
+    winner: Option<Color>,
+    phase: Phase,
+    // phase before last tick, frames drawn b/w two ticks blend it with current one.
+    previous: Phase,
+}
+
+impl Default for LudoState {
+    fn default() -> Self {
+        LudoState {
+            local: Vec::new(),
+            players: Vec::new(),
+            // until game starts, every color waits at home.
+            tokens: tokens_of(&Color::ALL),
+            turn: None,
+            last_roll: None,
+            legal_moves: Vec::new(),
+            seed_hash: None,
+            client_seeds: Vec::new(),
+            rolls: Vec::new(),
+            started: false,
+            winner: None,
+            phase: Phase::Waiting,
+            previous: Phase::Waiting,
+        }
+    }
+}
+
+impl LudoState {
+    pub fn seat_local(&mut self, color: Color) {
+        if !self.local.contains(&color) {
+            self.local.push(color);
+        }
+    }
+
+    pub fn is_local(&self, color: &Color) -> bool {
+        self.local.contains(color)
+    }
+
+    /// Seated players, in order they joined until game starts, then in order of turns.
+    pub fn players(&self) -> &[Color] {
+        &self.players
+    }
+
+    pub fn has_started(&self) -> bool {
+        self.started
+    }
+
+    pub fn tokens(&self) -> &[Token] {
+        &self.tokens
+    }
+
+    /// Token of local player can be moved with dice it has rolled.
+    pub fn can_move(&self, token_id: u8) -> bool {
+        self.turn.as_ref().is_some_and(|turn| self.is_local(turn))
+            && self
+                .legal_moves
+                .iter()
+                .any(|legal| legal.token_id == token_id)
+    }
+
+    /// Checks every roll seen in this game against seed server revealed at its end.
+    pub fn verify_dice(&self, server_seed: &str) -> Result<()> {
+        let seed_hash = self
+            .seed_hash
+            .as_ref()
+            .ok_or_else(|| anyhow!("Server never committed to a seed"))?;
+        fair_dice::verify(seed_hash, server_seed, &self.client_seeds, &self.rolls)
+    }
+
+    pub fn apply(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::PlayerJoined { color } => {
+                if !self.players.contains(color) {
+                    self.players.push(color.clone());
+                }
+            }
+            GameEvent::PlayerLeft { color } => self.players.retain(|player| player != color),
+            GameEvent::GameStarted { players } => {
+                self.players = players.clone();
+                self.tokens = tokens_of(players);
+                self.started = true;
+                self.winner = None;
+            }
+            GameEvent::DiceCommitted {
+                seed_hash,
+                client_seeds,
+            } => {
+                self.seed_hash = Some(seed_hash.clone());
+                self.client_seeds = client_seeds.clone();
+                self.rolls.clear();
+            }
+            // checked by whoever applies it, see `verify_dice`.
+            GameEvent::DiceRevealed { .. } => (),
+            GameEvent::DiceRolled { color, dice } => {
+                self.rolls.push(*dice);
+                self.last_roll = Some((color.clone(), *dice));
+                self.phase = Phase::Rolling { angle: 0. };
+            }
+            GameEvent::YourTurn { legal_moves, .. } => self.legal_moves = legal_moves.clone(),
+            GameEvent::TurnPassed { .. } => self.legal_moves.clear(),
+            GameEvent::TokenMoved {
+                color,
+                token_id,
+                status,
+            } => {
+                self.legal_moves.clear();
+                if let Some(token) = self
+                    .tokens
+                    .iter_mut()
+                    .find(|token| token.color() == color && token.id() == *token_id)
+                {
+                    let from = token.status().clone();
+                    *token = token.clone().with_staus(status.clone());
+                    // tokens it captured go straight home, and do not cut its walk short.
+                    if self.turn.as_ref() == Some(color) {
+                        self.phase = Phase::Animating {
+                            color: color.clone(),
+                            token_id: *token_id,
+                            from,
+                            progress: 0.,
+                        };
+                    }
+                }
+            }
+            GameEvent::TurnChanged { color } => {
+                self.turn = Some(color.clone());
+                self.legal_moves.clear();
+                if let Phase::ChoosingToken { .. } = self.phase {
+                    self.phase = Phase::Waiting;
+                }
+            }
+            // timed out turns show up through moves played for them.
+            GameEvent::TurnTimedOut { .. }
+            | GameEvent::PlayerAway { .. }
+            | GameEvent::PlayerReturned { .. }
+            | GameEvent::PlayerDisconnected { .. }
+            | GameEvent::PlayerReconnected { .. } => (),
+            GameEvent::GameCompleted { winner } => {
+                self.winner = Some(winner.clone());
+                self.turn = None;
+            }
+        }
+    }
+
+    /// Moves animation dt seconds forward. Returns true while there is more to animate.
+    pub fn tick(&mut self, dt: f32) -> bool {
+        self.previous = self.phase.clone();
+        match &mut self.phase {
+            Phase::Rolling { angle } => {
+                *angle += DICE_SPEED * dt;
+                if *angle >= DICE_SPIN {
+                    self.phase = self.after_roll();
+                }
+            }
+            Phase::Animating { progress, .. } => {
+                *progress += TOKEN_SPEED * dt;
+                if *progress >= 1. {
+                    self.phase = Phase::Waiting;
+                }
+            }
+            Phase::Waiting | Phase::ChoosingToken { .. } => (),
+        }
+        self.is_animating()
+    }
+
+    pub fn is_animating(&self) -> bool {
+        matches!(self.phase, Phase::Rolling { .. } | Phase::Animating { .. })
+    }
+
+    /// Phase as seen alpha (0 to 1) of the way from last tick to next one.
+    pub fn phase_at(&self, alpha: f32) -> Phase {
+        let blend = |from: f32, to: f32| from + (to - from) * alpha;
+        // a new roll or move starts over from zero, and is not blended with one before it.
+        match (&self.previous, &self.phase) {
+            (Phase::Rolling { angle: from }, Phase::Rolling { angle: to }) if from <= to => {
+                Phase::Rolling {
+                    angle: blend(*from, *to),
+                }
+            }
+            (
+                Phase::Animating { progress: from, .. },
+                Phase::Animating {
+                    color,
+                    token_id,
+                    from: status,
+                    progress: to,
+                },
+            ) if from <= to => Phase::Animating {
+                color: color.clone(),
+                token_id: *token_id,
+                from: status.clone(),
+                progress: blend(*from, *to),
+            },
+            (_, phase) => phase.clone(),
+        }
+    }
+
+    // once dice stops, local player picks a token if server listed any move for rolled value.
+    fn after_roll(&self) -> Phase {
+        match (&self.turn, &self.last_roll) {
+            (Some(turn), Some((rolled_by, dice)))
+                if turn == rolled_by && self.is_local(turn) && !self.legal_moves.is_empty() =>
+            {
+                Phase::ChoosingToken { dice: *dice }
+            }
+            _ => Phase::Waiting,
+        }
+    }
+}
+
+fn tokens_of(colors: &[Color]) -> Vec<Token> {
+    colors
+        .iter()
+        .flat_map(|color| (1..=4).map(move |id| Token::new(color.clone(), id)))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn tick_until_still(state: &mut LudoState) {
+        while state.tick(1. / 60.) {}
+    }
+
+    #[test]
+    fn test_events_drive_phase() {
+        let mut state = LudoState::default();
+        state.seat_local(Color::Yellow);
+        state.apply(&GameEvent::GameStarted {
+            players: vec![Color::Yellow, Color::Red],
+        });
+        state.apply(&GameEvent::TurnChanged {
+            color: Color::Yellow,
+        });
+        assert_eq!(state.tokens().len(), 8);
+
+        state.apply(&GameEvent::DiceRolled {
+            color: Color::Yellow,
+            dice: 6,
+        });
+        state.apply(&GameEvent::YourTurn {
+            color: Color::Yellow,
+            dice: 6,
+            legal_moves: vec![LegalMove {
+                token_id: 2,
+                status: Status::Running { pos: 1 },
+                position: Some((6, 1)),
+                captures: false,
+                safe: true,
+                finishes: false,
+            }],
+        });
+        assert!(state.can_move(2));
+        assert!(!state.can_move(1));
+        assert!(state.is_animating());
+        tick_until_still(&mut state);
+        assert_eq!(state.phase, Phase::ChoosingToken { dice: 6 });
+
+        state.apply(&GameEvent::TokenMoved {
+            color: Color::Yellow,
+            token_id: 2,
+            status: Status::Running { pos: 1 },
+        });
+        tick_until_still(&mut state);
+        assert_eq!(state.phase, Phase::Waiting);
+        assert!(!state.can_move(2));
+        assert_eq!(state.tokens()[1].status(), &Status::Running { pos: 1 });
+    }
+
+    #[test]
+    fn test_capture_keeps_mover_walking() {
+        let mut state = LudoState::default();
+        state.apply(&GameEvent::GameStarted {
+            players: vec![Color::Yellow, Color::Red],
+        });
+        state.apply(&GameEvent::TurnChanged {
+            color: Color::Yellow,
+        });
+        state.apply(&GameEvent::TokenMoved {
+            color: Color::Yellow,
+            token_id: 2,
+            status: Status::Running { pos: 3 },
+        });
+        tick_until_still(&mut state);
+        state.apply(&GameEvent::TurnChanged { color: Color::Red });
+        state.apply(&GameEvent::TokenMoved {
+            color: Color::Red,
+            token_id: 1,
+            status: Status::Running { pos: 3 },
+        });
+        state.apply(&GameEvent::TokenMoved {
+            color: Color::Yellow,
+            token_id: 2,
+            status: Status::Home,
+        });
+        assert!(matches!(
+            state.phase,
+            Phase::Animating {
+                color: Color::Red,
+                token_id: 1,
+                ..
+            }
+        ));
+        assert_eq!(state.tokens()[1].status(), &Status::Home);
+    }
+}
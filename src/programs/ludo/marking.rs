@@ -0,0 +1,149 @@
+use std::f32::consts::PI;
+
+use crate::engine::mesh::Mesh;
+use crate::server::entity::{
+    color::Color as ServerColor,
+    token::{Status, Token as ServerToken},
+};
+
+use super::color::Color;
+use super::coordinate::Coordinate;
+use super::theme::Theme;
+use super::token::Token;
+
+// markings float a bit above what they are drawn on, so that they do not fight with it for depth.
+const LIFT: f32 = 0.01;
+const HOME_RADIUS: f32 = 0.45;
+const COLUMN_RADIUS: f32 = 0.35;
+const TOKEN_RADIUS: f32 = 0.2;
+const CIRCLE_SEGMENTS: usize = 16;
+// inner corners of star, as part of its outer radius.
+const STAR_INNER: f32 = 0.45;
+
+/// Shape carried by tokens, home and home column of a color,
+/// so that colors can be told apart without telling hues apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Marking {
+    Circle,
+    Square,
+    Triangle,
+    Star,
+}
+
+impl Marking {
+    // red and green, which are most often mixed up, get shapes least alike.
+    pub(super) fn of(color: &Color) -> Self {
+        match color {
+            Color::Yellow => Marking::Circle,
+            Color::Blue => Marking::Square,
+            Color::Red => Marking::Triangle,
+            Color::Green => Marking::Star,
+        }
+    }
+
+    // corners of shape around its center as (x, z), anti clock wise seen from above, fitting in a unit circle.
+    fn outline(&self) -> Vec<(f32, f32)> {
+        let corners = |count: usize, start: f32, radius: &dyn Fn(usize) -> f32| {
+            (0..count)
+                .map(|i| {
+                    let angle = start + 2. * PI * i as f32 / count as f32;
+                    let (sin, cos) = angle.sin_cos();
+                    (radius(i) * cos, -radius(i) * sin)
+                })
+                .collect()
+        };
+        match self {
+            Marking::Circle => corners(CIRCLE_SEGMENTS, 0., &|_| 1.),
+            Marking::Square => corners(4, PI / 4., &|_| 1.),
+            Marking::Triangle => corners(3, PI / 2., &|_| 1.),
+            Marking::Star => corners(10, PI / 2., &|i| if i % 2 == 0 { 1. } else { STAR_INNER }),
+        }
+    }
+
+    /// Flat shape facing up, made of a fan of triangles around its center.
+    pub(super) fn append_to(
+        &self,
+        mesh: &mut Mesh,
+        (x, y, z): (f32, f32, f32),
+        radius: f32,
+        color: [f32; 3],
+    ) {
+        let begin = mesh.vertex_count() as u16;
+        let outline = self.outline();
+        for (dx, dz) in std::iter::once((0., 0.)).chain(outline.iter().cloned()) {
+            mesh.vertices
+                .extend_from_slice(&[x + dx * radius, y, z + dz * radius]);
+            mesh.normals.extend_from_slice(&[0., 1., 0.]);
+            mesh.colors.extend_from_slice(&color);
+        }
+        let corners = outline.len() as u16;
+        for i in 0..corners {
+            mesh.indices
+                .extend_from_slice(&[begin, begin + 1 + i, begin + 1 + (i + 1) % corners]);
+        }
+    }
+}
+
+/// Markings lying on board, one in middle of each home and one on every tile of each home column.
+pub(super) fn board_mesh(coordinate: &Coordinate, theme: &Theme) -> Mesh {
+    let mut mesh = Mesh::default();
+    let y = coordinate.top + 0.15 + LIFT;
+    for server_color in ServerColor::ALL.iter() {
+        let color = Color::from(server_color);
+        let marking = Marking::of(&color);
+
+        // home is plain, so marking takes color of player.
+        let (x, z) = (1..=4)
+            .map(|id| {
+                let token = ServerToken::new(server_color.clone(), id);
+                Token::spot(&token, &Status::Home, coordinate)
+            })
+            .fold((0., 0.), |(x, z), (spot_x, spot_z)| {
+                (x + spot_x / 4., z + spot_z / 4.)
+            });
+        marking.append_to(&mut mesh, (x, y, z), HOME_RADIUS, theme.color(&color));
+
+        let token = ServerToken::new(server_color.clone(), 1);
+        for pos in 1..=5 {
+            let (x, z) = Token::spot(&token, &Status::FinalWalk { pos }, coordinate);
+            marking.append_to(
+                &mut mesh,
+                (x, y, z),
+                COLUMN_RADIUS,
+                theme.marking_color(&color),
+            );
+        }
+    }
+    mesh
+}
+
+/// Marking resting on top of a token standing on origin.
+pub(super) fn token_mesh(color: &Color, theme: &Theme) -> Mesh {
+    let mut mesh = Mesh::default();
+    Marking::of(color).append_to(
+        &mut mesh,
+        (0., Token::height() + LIFT, 0.),
+        TOKEN_RADIUS,
+        theme.marking_color(color),
+    );
+    mesh
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_every_color_has_its_own_marking() {
+        let colors = [Color::Red, Color::Green, Color::Yellow, Color::Blue];
+        for (i, color) in colors.iter().enumerate() {
+            for other in &colors[i + 1..] {
+                assert_ne!(Marking::of(color), Marking::of(other));
+            }
+            assert!(token_mesh(color, &Theme::default()).validate().is_ok());
+        }
+        let board = board_mesh(&Coordinate::for_board(), &Theme::default());
+        assert!(board.validate().is_ok());
+        assert!(board.normals.chunks(3).all(|normal| normal == [0., 1., 0.]));
+    }
+}
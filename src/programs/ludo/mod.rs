@@ -1,8 +1,14 @@
 mod board_configuration;
+pub mod camera;
 pub mod color;
 mod coordinate;
-mod dice_state;
+mod dice;
+mod light;
 pub mod ludo_program;
-mod ludo_state;
+pub mod ludo_state;
+mod marking;
 mod position;
+mod scene;
+pub mod shadow_map;
+pub mod theme;
 mod token;
@@ -226,4 +226,15 @@ impl Position {
         }
         vertices
     }
+
+    /// Center of four inner blocks of cornor square, this is where tokens wait at home.
+    pub(super) fn home_spots(&self, board_coordinate: &Coordinate) -> [(f32, f32); 4] {
+        let inner_blocks = self.conor_sq_inner_block(board_coordinate);
+        let mut spots = [(0., 0.); 4];
+        for (spot, block) in spots.iter_mut().zip(inner_blocks.chunks(12)) {
+            // block is left bottom, right bottom, right up and left up corner.
+            *spot = ((block[0] + block[3]) / 2., (block[2] + block[8]) / 2.);
+        }
+        spots
+    }
 }
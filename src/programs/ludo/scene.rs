@@ -0,0 +1,292 @@
+use std::rc::Rc;
+
+use nalgebra::{Matrix4, Point3, Vector3};
+use web_sys::{WebGlRenderingContext as GL, WebGlTexture};
+
+use crate::engine::assets::Assets;
+use crate::engine::material::{Material, Uniform};
+use crate::engine::mesh::{GpuMesh, Mesh};
+use crate::engine::renderer::Renderer;
+use crate::engine::scene::Node;
+use anyhow::Result;
+
+use super::board_configuration::BoardConfiguration;
+use super::camera::Camera;
+use super::color::Color;
+use super::coordinate::Coordinate;
+use super::dice;
+use super::light::Light;
+use super::marking::{self, Marking};
+use super::shadow_map::ShadowMap;
+use super::theme::Theme;
+use super::token::Token;
+
+const TINT: &str = "u_Tint";
+const SHININESS: &str = "u_Shininess";
+const NO_TINT: [f32; 4] = [1., 1., 1., 1.];
+// higher is smaller and sharper highlight. Board is matte, dice and tokens are polished.
+const BOARD_SHININESS: f32 = 8.;
+const DICE_SHININESS: f32 = 32.;
+const TOKEN_SHININESS: f32 = 64.;
+// names of textures in asset manifest.
+const BOARD_TEXTURE: &str = "board";
+const DICE_TEXTURE: &str = "dice";
+const TOKEN_TEXTURE: &str = "token";
+// token nodes are made for four tokens of each color, in this order.
+const TOKEN_COLORS: [Color; 4] = [Color::Red, Color::Green, Color::Yellow, Color::Blue];
+const TOKENS_PER_COLOR: usize = 4;
+
+/// Everything drawn for a ludo game. Board never changes,
+/// dice and tokens only change their transform, and tokens whether they are seen, from frame to frame.
+pub(super) struct LudoScene {
+    light: Light,
+    light_view_projection: Matrix4<f32>,
+    root: Node,
+    board: usize,
+    dice: usize,
+    tokens: usize,
+    // drawn on everything until textures are loaded, and always under markings.
+    white: WebGlTexture,
+    theme: Theme,
+    // where dice rests on board, before it is moved around.
+    dice_rest: Matrix4<f32>,
+}
+
+impl LudoScene {
+    /// Colors of board and dice are baked into their meshes, so a new theme needs a new scene.
+    /// Markings put a shape of each color on its tokens, home and home column.
+    /// White texture stands in for textures which have not loaded yet.
+    pub(super) fn new(
+        gl: &GL,
+        coorinate: &Coordinate,
+        theme: Theme,
+        markings: bool,
+        white: WebGlTexture,
+    ) -> Result<Self> {
+        let mut root = Node::group();
+
+        let BoardConfiguration {
+            vertices,
+            normals,
+            indices,
+            colors,
+        } = BoardConfiguration::new(coorinate, &Color::Yellow, &theme);
+        let board = Mesh {
+            uv: top_down_uv(&vertices, coorinate),
+            vertices,
+            normals,
+            colors,
+            indices,
+            ..Default::default()
+        };
+        let mut board = Node::with_mesh(Rc::new(GpuMesh::new(gl, &board)?));
+        board.texture = Some(white.clone());
+        board.set_uniform(TINT, Uniform::Vec4(NO_TINT));
+        board.set_uniform(SHININESS, Uniform::Float(BOARD_SHININESS));
+        let board = root.add_child(board);
+        let mut token_markings = Vec::new();
+        if markings {
+            let mut board_markings = Node::with_mesh(Rc::new(GpuMesh::new(
+                gl,
+                &marking::board_mesh(coorinate, &theme),
+            )?));
+            board_markings.set_uniform(TINT, Uniform::Vec4(NO_TINT));
+            board_markings.texture = Some(white.clone());
+            root.add_child(board_markings);
+            for color in [Color::Red, Color::Green, Color::Yellow, Color::Blue] {
+                let mesh = GpuMesh::new(gl, &marking::token_mesh(&color, &theme))?;
+                token_markings.push((Marking::of(&color), Rc::new(mesh)));
+            }
+        }
+
+        let dice_coordinate = Coordinate::for_dice();
+        let dice_rest = Matrix4::new_translation(&Vector3::new(
+            (dice_coordinate.left + dice_coordinate.right) / 2.,
+            (dice_coordinate.top + dice_coordinate.bottom) / 2.,
+            (dice_coordinate.near + dice_coordinate.far) / 2.,
+        ));
+        let mut dice = Node::with_mesh(Rc::new(GpuMesh::new(gl, &dice::mesh(&theme))?));
+        dice.transform = dice_rest;
+        dice.casts_shadow = true;
+        dice.texture = Some(white.clone());
+        dice.set_uniform(TINT, Uniform::Vec4(NO_TINT));
+        dice.set_uniform(SHININESS, Uniform::Float(DICE_SHININESS));
+        let dice = root.add_child(dice);
+
+        // all tokens share one white mesh, tint gives them their color.
+        let token_mesh = Rc::new(GpuMesh::new(gl, &Token::mesh())?);
+        let mut tokens = Node::group();
+        tokens.set_uniform(SHININESS, Uniform::Float(TOKEN_SHININESS));
+        for color in TOKEN_COLORS.iter() {
+            let [r, g, b] = theme.color(color);
+            let marking = Marking::of(color);
+            for _ in 0..TOKENS_PER_COLOR {
+                let mut node = Node::with_mesh(token_mesh.clone());
+                node.casts_shadow = true;
+                node.texture = Some(white.clone());
+                node.set_uniform(TINT, Uniform::Vec4([r, g, b, 1.]));
+                if let Some((_, mesh)) = token_markings.iter().find(|(of, _)| *of == marking) {
+                    // marking has its own colors, and is not tinted like token under it.
+                    let mut marking = Node::with_mesh(mesh.clone());
+                    marking.set_uniform(TINT, Uniform::Vec4(NO_TINT));
+                    marking.texture = Some(white.clone());
+                    node.add_child(marking);
+                }
+                tokens.add_child(node);
+            }
+        }
+        let tokens = root.add_child(tokens);
+
+        let light = Light::default();
+        let center = Point3::new(
+            (coorinate.left + coorinate.right) / 2.,
+            coorinate.top,
+            (coorinate.near + coorinate.far) / 2.,
+        );
+        let mut scene = LudoScene {
+            light_view_projection: light.view_projection(&center),
+            light,
+            root,
+            board,
+            dice,
+            tokens,
+            white,
+            theme,
+            dice_rest,
+        };
+        scene.set_tokens(
+            BoardConfiguration::position_color_map(&Color::Yellow)
+                .iter()
+                .flat_map(|(position, color)| Token::at_home(color, position, coorinate)),
+        );
+        Ok(scene)
+    }
+
+    /// Moves a node of its color to each token, nodes no token is moved to are hidden.
+    pub(super) fn set_tokens(&mut self, tokens: impl IntoIterator<Item = Token>) {
+        let group = match self.root.child_mut(self.tokens) {
+            Some(group) => group,
+            None => return,
+        };
+        for node in group.children.iter_mut() {
+            node.visible = false;
+        }
+        // nodes of each color already moved to a token.
+        let mut placed = [0; TOKEN_COLORS.len()];
+        for token in tokens {
+            let slot = token_slot(token.color());
+            if placed[slot] == TOKENS_PER_COLOR {
+                continue;
+            }
+            if let Some(node) = group.child_mut(slot * TOKENS_PER_COLOR + placed[slot]) {
+                node.transform = token.model_matrix();
+                node.visible = true;
+            }
+            placed[slot] += 1;
+        }
+    }
+
+    /// Puts loaded textures on board, dice and tokens. Ones still loading stay white.
+    pub(super) fn set_textures(&mut self, assets: &Assets) {
+        let texture = |name| assets.texture(name).unwrap_or_else(|| self.white.clone());
+        let (board, dice, token) = (
+            texture(BOARD_TEXTURE),
+            texture(DICE_TEXTURE),
+            texture(TOKEN_TEXTURE),
+        );
+        if let Some(node) = self.root.child_mut(self.board) {
+            node.texture = Some(board);
+        }
+        if let Some(node) = self.root.child_mut(self.dice) {
+            node.texture = Some(dice);
+        }
+        if let Some(group) = self.root.child_mut(self.tokens) {
+            for node in group.children.iter_mut() {
+                node.texture = Some(token.clone());
+            }
+        }
+    }
+
+    pub(super) fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// Moves dice away from where it rests, transform is in board space.
+    pub(super) fn set_dice_transform(&mut self, transform: &Matrix4<f32>) {
+        let dice_rest = self.dice_rest;
+        if let Some(dice) = self.root.child_mut(self.dice) {
+            dice.transform = transform * dice_rest;
+        }
+    }
+
+    /// Renders depth of dice and tokens into shadow map. Board only receives shadows.
+    pub(super) fn draw_shadow_casters(&self, gl: &GL, shadow_map: &ShadowMap) {
+        Renderer::new(self.light_view_projection).render_shadow_casters(
+            gl,
+            shadow_map.material(),
+            &self.root,
+        );
+    }
+
+    pub(super) fn draw(
+        &self,
+        gl: &GL,
+        material: &Material,
+        camera: &Camera,
+        shadow_map: Option<&ShadowMap>,
+    ) {
+        material.use_program(gl);
+        material.set(gl, "u_LightColor", &Uniform::Vec3(self.light.color));
+        material.set(
+            gl,
+            "u_LightDirection",
+            &Uniform::Vec3(self.light.direction.into()),
+        );
+        material.set(gl, "u_AmbientLight", &Uniform::Vec3(self.light.ambient));
+        material.set(gl, "u_Texture", &Uniform::Int(0));
+        material.set(
+            gl,
+            "u_EyePosition",
+            &Uniform::Vec3(camera.eye().coords.into()),
+        );
+        match shadow_map {
+            Some(shadow_map) => {
+                // unit 0 is left for regular textures.
+                gl.active_texture(GL::TEXTURE1);
+                gl.bind_texture(GL::TEXTURE_2D, Some(shadow_map.texture()));
+                gl.active_texture(GL::TEXTURE0);
+                material.set(gl, "u_ShadowMap", &Uniform::Int(1));
+                material.set(gl, "u_ShadowEnabled", &Uniform::Float(1.));
+                material.set(gl, "u_ShadowTexel", &Uniform::Float(shadow_map.texel()));
+            }
+            None => material.set(gl, "u_ShadowEnabled", &Uniform::Float(0.)),
+        }
+
+        Renderer::new(camera.view_projection())
+            .with_light_view_projection(self.light_view_projection)
+            .render(gl, material, &self.root);
+    }
+}
+
+// index of color in `TOKEN_COLORS`.
+fn token_slot(color: &Color) -> usize {
+    match color {
+        Color::Red => 0,
+        Color::Green => 1,
+        Color::Yellow => 2,
+        Color::Blue => 3,
+    }
+}
+
+// board is seen from above, so its texture is laid flat over whole of it.
+fn top_down_uv(vertices: &[f32], coordinate: &Coordinate) -> Vec<f32> {
+    vertices
+        .chunks(3)
+        .flat_map(|vertex| {
+            [
+                (vertex[0] - coordinate.left) / coordinate.width(),
+                (vertex[2] - coordinate.far) / coordinate.depth(),
+            ]
+        })
+        .collect()
+}
@@ -0,0 +1,149 @@
+use anyhow::{anyhow, Result};
+use web_sys::{WebGlFramebuffer, WebGlRenderbuffer, WebGlRenderingContext as GL, WebGlTexture};
+
+use crate::engine::material::Material;
+use crate::shaders::fragment::shadow_shader as FS;
+use crate::shaders::vertex::shadow_shader as VS;
+
+/// How good shadows look, weak devices are better off without them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShadowQuality {
+    Off,
+    Low,
+    High,
+}
+
+impl ShadowQuality {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "off" => Some(ShadowQuality::Off),
+            "low" => Some(ShadowQuality::Low),
+            "high" => Some(ShadowQuality::High),
+            _ => None,
+        }
+    }
+
+    // width and height of shadow map texture.
+    fn size(&self) -> Option<i32> {
+        match self {
+            ShadowQuality::Off => None,
+            ShadowQuality::Low => Some(1024),
+            ShadowQuality::High => Some(2048),
+        }
+    }
+}
+
+/// Depth of scene as seen from light, rendered into a texture.
+/// Main pass compares its own depth from light with this to find out what is in shadow.
+pub(super) struct ShadowMap {
+    material: Material,
+    framebuffer: WebGlFramebuffer,
+    texture: WebGlTexture,
+    // kept alive for as long as framebuffer uses it.
+    _depth: WebGlRenderbuffer,
+    size: i32,
+}
+
+impl ShadowMap {
+    /// None when shadows are turned off.
+    pub(super) fn new(gl: &GL, quality: ShadowQuality) -> Result<Option<Self>> {
+        let size = match quality.size() {
+            Some(size) => size,
+            None => return Ok(None),
+        };
+        let material = Material::new(gl, VS::SHADOW_VERTEX_SHADER, FS::SHADOW_FRAGMENT_SHADER)?;
+
+        let texture = gl
+            .create_texture()
+            .ok_or_else(|| anyhow!("Failed to create shadow texture"))?;
+        gl.bind_texture(GL::TEXTURE_2D, Some(&texture));
+        gl.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+            GL::TEXTURE_2D,
+            0,
+            GL::RGBA as i32,
+            size,
+            size,
+            0,
+            GL::RGBA,
+            GL::UNSIGNED_BYTE,
+            None,
+        )
+        .map_err(|err| anyhow!(format!("Failed to allocate shadow texture {:#?}", err)))?;
+        // packed depth can not be interpolated.
+        gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_MIN_FILTER, GL::NEAREST as i32);
+        gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_MAG_FILTER, GL::NEAREST as i32);
+        gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_WRAP_S, GL::CLAMP_TO_EDGE as i32);
+        gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_WRAP_T, GL::CLAMP_TO_EDGE as i32);
+
+        let depth = gl
+            .create_renderbuffer()
+            .ok_or_else(|| anyhow!("Failed to create shadow depth buffer"))?;
+        gl.bind_renderbuffer(GL::RENDERBUFFER, Some(&depth));
+        gl.renderbuffer_storage(GL::RENDERBUFFER, GL::DEPTH_COMPONENT16, size, size);
+
+        let framebuffer = gl
+            .create_framebuffer()
+            .ok_or_else(|| anyhow!("Failed to create shadow framebuffer"))?;
+        gl.bind_framebuffer(GL::FRAMEBUFFER, Some(&framebuffer));
+        gl.framebuffer_texture_2d(
+            GL::FRAMEBUFFER,
+            GL::COLOR_ATTACHMENT0,
+            GL::TEXTURE_2D,
+            Some(&texture),
+            0,
+        );
+        gl.framebuffer_renderbuffer(
+            GL::FRAMEBUFFER,
+            GL::DEPTH_ATTACHMENT,
+            GL::RENDERBUFFER,
+            Some(&depth),
+        );
+        let status = gl.check_framebuffer_status(GL::FRAMEBUFFER);
+
+        gl.bind_framebuffer(GL::FRAMEBUFFER, None);
+        gl.bind_renderbuffer(GL::RENDERBUFFER, None);
+        gl.bind_texture(GL::TEXTURE_2D, None);
+        if status != GL::FRAMEBUFFER_COMPLETE {
+            return Err(anyhow!(format!(
+                "Shadow framebuffer is incomplete with status {}",
+                status
+            )));
+        }
+
+        Ok(Some(ShadowMap {
+            material,
+            framebuffer,
+            texture,
+            _depth: depth,
+            size,
+        }))
+    }
+
+    /// Directs drawing into shadow map. Everything drawn with its material till `end` casts shadow.
+    pub(super) fn begin(&self, gl: &GL) {
+        gl.bind_framebuffer(GL::FRAMEBUFFER, Some(&self.framebuffer));
+        gl.viewport(0, 0, self.size, self.size);
+        // farthest possible depth, nothing is in shadow until some caster is drawn.
+        gl.clear_color(1., 1., 1., 1.);
+        gl.enable(GL::DEPTH_TEST);
+        gl.clear(GL::COLOR_BUFFER_BIT | GL::DEPTH_BUFFER_BIT);
+    }
+
+    pub(super) fn material(&self) -> &Material {
+        &self.material
+    }
+
+    /// Sends drawing back to canvas.
+    pub(super) fn end(&self, gl: &GL) {
+        gl.bind_framebuffer(GL::FRAMEBUFFER, None);
+        gl.viewport(0, 0, gl.drawing_buffer_width(), gl.drawing_buffer_height());
+    }
+
+    pub(super) fn texture(&self) -> &WebGlTexture {
+        &self.texture
+    }
+
+    pub(super) fn texel(&self) -> f32 {
+        1. / self.size as f32
+    }
+}
@@ -0,0 +1,105 @@
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+
+use super::color::Color;
+
+/// Colors board, tokens and dice are drawn with. Each channel is b/w 0 and 1.
+/// A theme file only needs colors it changes, rest are taken from classic theme.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    pub name: String,
+    pub red: [f32; 3],
+    pub green: [f32; 3],
+    pub yellow: [f32; 3],
+    pub blue: [f32; 3],
+    /// Plain tiles and inside of home squares.
+    pub board: [f32; 3],
+    /// Shows through gaps b/w tiles.
+    pub lines: [f32; 3],
+    pub dice: [f32; 3],
+    pub pips: [f32; 3],
+    pub clear_color: [f32; 4],
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::classic()
+    }
+}
+
+impl Theme {
+    pub fn classic() -> Self {
+        Theme {
+            name: "Classic".to_owned(),
+            red: [1., 0., 0.],
+            green: [0., 1., 0.],
+            yellow: [1., 1., 0.],
+            blue: [0., 0., 1.],
+            board: [1., 1., 1.],
+            lines: [0., 0., 0.],
+            dice: [0.95, 0.95, 0.95],
+            pips: [0.1, 0.1, 0.1],
+            clear_color: [0., 0., 0., 1.],
+        }
+    }
+
+    /// Okabe-Ito palette, its colors stay apart for every common kind of color blindness.
+    pub fn colorblind() -> Self {
+        Theme {
+            name: "Colorblind".to_owned(),
+            red: [0.835, 0.369, 0.],
+            green: [0., 0.620, 0.451],
+            yellow: [0.941, 0.894, 0.259],
+            blue: [0., 0.447, 0.698],
+            board: [0.97, 0.97, 0.97],
+            lines: [0.2, 0.2, 0.2],
+            dice: [0.95, 0.95, 0.95],
+            pips: [0.1, 0.1, 0.1],
+            clear_color: [0.15, 0.15, 0.15, 1.],
+        }
+    }
+
+    /// Themes which are always there, without loading any file.
+    pub fn built_in() -> Vec<Theme> {
+        vec![Theme::classic(), Theme::colorblind()]
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).map_err(|err| anyhow!("Failed to parse theme {}", err))
+    }
+
+    pub fn color(&self, color: &Color) -> [f32; 3] {
+        match color {
+            Color::Red => self.red,
+            Color::Green => self.green,
+            Color::Yellow => self.yellow,
+            Color::Blue => self.blue,
+        }
+    }
+
+    /// Board or line color, whichever stands out more on given player color.
+    pub fn marking_color(&self, color: &Color) -> [f32; 3] {
+        let [r, g, b] = self.color(color);
+        if 0.2126 * r + 0.7152 * g + 0.0722 * b > 0.5 {
+            self.lines
+        } else {
+            self.board
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_theme_file_overrides_classic() {
+        let theme =
+            Theme::from_json(r#"{"name": "Night", "clear_color": [0, 0, 0.2, 1]}"#).unwrap();
+        assert_eq!(theme.name, "Night");
+        assert_eq!(theme.clear_color, [0., 0., 0.2, 1.]);
+        assert_eq!(theme.color(&Color::Red), Theme::classic().red);
+        assert!(Theme::from_json(r#"{"red": "red"}"#).is_err());
+    }
+}
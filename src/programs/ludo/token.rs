@@ -1,9 +1,35 @@
-use super::{color::Color, coordinate::Coordinate};
+use std::f32::consts::PI;
 
-pub(super) struct Token<'a> {
+use nalgebra::{Matrix4, Vector2, Vector3};
+
+use crate::engine::mesh::Mesh;
+
+use crate::server::entity::{
+    color::Color as ServerColor,
+    token::{Status, Token as ServerToken},
+};
+
+use super::{color::Color, coordinate::Coordinate, position::Position};
+
+// board is 15 tiles wide and deep.
+const TILES: f32 = 15.;
+// number of sides of token around y axis.
+const SEGMENTS: u16 = 16;
+// outline of token from bottom to top as (radius, height), it is spun around y axis.
+const PROFILE: [(f32, f32); 8] = [
+    (0.45, 0.),
+    (0.45, 0.12),
+    (0.22, 0.3),
+    (0.12, 0.75),
+    (0.25, 0.85),
+    (0.28, 0.98),
+    (0.2, 1.12),
+    (0., 1.18),
+];
+
+pub(super) struct Token {
     color: Color,
     position: TokenPosition,
-    board_coordinate: &'a Coordinate,
 }
 
 struct TokenPosition {
@@ -11,3 +37,139 @@ struct TokenPosition {
     y: f32,
     z: f32,
 }
+
+impl Token {
+    /// All four tokens of color waiting in home cornor of given position.
+    pub(super) fn at_home(
+        color: &Color,
+        position: &Position,
+        board_coordinate: &Coordinate,
+    ) -> Vec<Token> {
+        position
+            .home_spots(board_coordinate)
+            .iter()
+            .map(|spot| Token::standing_at(color, *spot, board_coordinate))
+            .collect()
+    }
+
+    /// Token standing on board at given (x, z).
+    pub(super) fn standing_at(
+        color: &Color,
+        (x, z): (f32, f32),
+        board_coordinate: &Coordinate,
+    ) -> Token {
+        Token {
+            color: color.clone(),
+            position: TokenPosition {
+                x,
+                y: board_coordinate.top + 0.15,
+                z,
+            },
+        }
+    }
+
+    /// (x, z) where token with given status stands, board is laid out with yellow on left near cornor.
+    pub(super) fn spot(
+        token: &ServerToken,
+        status: &Status,
+        board_coordinate: &Coordinate,
+    ) -> (f32, f32) {
+        let token = token.clone().with_staus(status.clone());
+        match token.get_x_z() {
+            Some((x, z)) => (
+                board_coordinate.left + (x as f32 + 0.5) * board_coordinate.width() / TILES,
+                board_coordinate.near - (z as f32 + 0.5) * board_coordinate.depth() / TILES,
+            ),
+            None => {
+                let position = match token.color() {
+                    ServerColor::Yellow => Position::LeftNear,
+                    ServerColor::Blue => Position::LeftFar,
+                    ServerColor::Red => Position::RightFar,
+                    ServerColor::Green => Position::RightNear,
+                };
+                position.home_spots(board_coordinate)[token.id() as usize - 1]
+            }
+        }
+    }
+
+    /// Height of token standing on origin.
+    pub(super) fn height() -> f32 {
+        PROFILE[PROFILE.len() - 1].1
+    }
+
+    pub(super) fn color(&self) -> &Color {
+        &self.color
+    }
+
+    pub(super) fn model_matrix(&self) -> Matrix4<f32> {
+        let TokenPosition { x, y, z } = self.position;
+        Matrix4::new_translation(&Vector3::new(x, y, z))
+    }
+
+    /// Token standing on origin. Colors are white, so that same mesh can be tinted for every player.
+    /// Texture wraps once around it, from bottom to top of its outline.
+    pub(super) fn mesh() -> Mesh {
+        let mut vertices = Vec::<f32>::new();
+        let mut normals = Vec::<f32>::new();
+        let mut uv = Vec::<f32>::new();
+        let mut indices = Vec::<u16>::new();
+        let ring_size = SEGMENTS + 1;
+        for (ring, (radius, height)) in PROFILE.iter().enumerate() {
+            let (normal_r, normal_y) = profile_normal(ring);
+            for i in 0..ring_size {
+                let angle = 2. * PI * i as f32 / SEGMENTS as f32;
+                let (sin, cos) = angle.sin_cos();
+                vertices.extend_from_slice(&[radius * cos, *height, -radius * sin]);
+                normals.extend_from_slice(&[normal_r * cos, normal_y, -normal_r * sin]);
+                uv.extend_from_slice(&[
+                    i as f32 / SEGMENTS as f32,
+                    ring as f32 / (PROFILE.len() - 1) as f32,
+                ]);
+            }
+        }
+
+        for ring in 0..PROFILE.len() as u16 - 1 {
+            let lower = ring * ring_size;
+            let upper = lower + ring_size;
+            for i in 0..SEGMENTS {
+                indices.extend_from_slice(&[
+                    lower + i,
+                    lower + i + 1,
+                    upper + i + 1,
+                    lower + i,
+                    upper + i + 1,
+                    upper + i,
+                ]);
+            }
+        }
+
+        let colors = vec![1.; vertices.len()];
+        Mesh {
+            vertices,
+            normals,
+            colors,
+            uv,
+            indices,
+            ..Default::default()
+        }
+    }
+}
+
+// smooth normal of a ring, average of outward normals of outline edges touching it.
+// It is returned as (radial, vertical) part.
+fn profile_normal(ring: usize) -> (f32, f32) {
+    let edge_normal = |from: usize, to: usize| {
+        let (r0, y0) = PROFILE[from];
+        let (r1, y1) = PROFILE[to];
+        Vector2::new(y1 - y0, r0 - r1).normalize()
+    };
+    let mut normal = Vector2::zeros();
+    if ring > 0 {
+        normal += edge_normal(ring - 1, ring);
+    }
+    if ring + 1 < PROFILE.len() {
+        normal += edge_normal(ring, ring + 1);
+    }
+    let normal = normal.normalize();
+    (normal.x, normal.y)
+}
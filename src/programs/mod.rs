@@ -1,5 +1,4 @@
 pub mod cube_program;
-pub(self) mod helper;
 pub(crate) mod ludo;
 pub mod point_program;
 pub mod texture_program;
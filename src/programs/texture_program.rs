@@ -1,83 +1,54 @@
 #![allow(dead_code)]
-use js_sys::{Float32Array, WebAssembly};
-use wasm_bindgen::JsCast;
-use web_sys::{HtmlImageElement, WebGlProgram, WebGlRenderingContext};
+use std::rc::Rc;
 
+use web_sys::{HtmlImageElement, WebGlRenderingContext, WebGlTexture};
+
+use crate::engine::material::{Material, Uniform};
+use crate::engine::mesh::{GpuMesh, Mesh, Primitive};
+use crate::engine::renderer::Renderer;
+use crate::engine::scene::Node;
 use crate::shaders::fragment::texture_shader as fragment;
 use crate::shaders::vertex::texture_shader as vertex;
 use anyhow::{anyhow, Ok, Result};
+use nalgebra::Matrix4;
 
-use super::helper::{attribute_location, link_program};
 use web_sys::WebGlRenderingContext as GL;
 
-const FLOAT_SIZE: i32 = std::mem::size_of::<f32>() as i32;
 pub struct TextureProgram {
-    pub program: WebGlProgram,
+    pub material: Material,
+    quad: Node,
 }
 
 impl TextureProgram {
     pub fn new(gl: &WebGlRenderingContext) -> Self {
-        let program = link_program(
+        let material = Material::new(
             gl,
             vertex::TEXTURE_VERTEX_SHADER,
             fragment::TEXTURE_FRAGMENT_SHADER,
         )
         .unwrap();
-        TextureProgram { program }
-    }
-    pub fn assing_attributes(&self, gl: &WebGlRenderingContext) -> Result<u32> {
-        let buffer_data: [f32; 16] = [
-            // vertex cood, texture coord
-            -0.5, 0.5, 0., 1., -0.5, -0.5, 0., 0., 0.5, 0.5, 1., 1., 0.5, -0.5, 1., 0.,
-        ];
-        let buffer = gl
-            .create_buffer()
-            .ok_or_else(|| anyhow!("Failed to create buffer"))?;
-        gl.bind_buffer(GL::ARRAY_BUFFER, Some(&buffer));
-
-        let gl_memory = wasm_bindgen::memory()
-            .dyn_into::<WebAssembly::Memory>()
-            .unwrap()
-            .buffer();
-        let buffer_data_location = buffer_data.as_ptr() as u32 / 4;
-        let js_memory = Float32Array::new(&gl_memory).subarray(
-            buffer_data_location,
-            buffer_data_location + buffer_data.len() as u32,
-        );
-        gl.buffer_data_with_array_buffer_view(GL::ARRAY_BUFFER, &js_memory, GL::STATIC_DRAW);
-
-        let a_position = attribute_location(&self.program, gl, "a_Position")?;
-        gl.vertex_attrib_pointer_with_i32(a_position, 2, GL::FLOAT, false, FLOAT_SIZE * 4, 0);
-        gl.enable_vertex_attrib_array(a_position);
-
-        let a_tex_coord = attribute_location(&self.program, gl, "a_TexCoord")?;
-        gl.vertex_attrib_pointer_with_i32(
-            a_tex_coord,
-            2,
-            GL::FLOAT,
-            false,
-            FLOAT_SIZE * 4,
-            FLOAT_SIZE * 2,
-        );
-        gl.enable_vertex_attrib_array(a_tex_coord);
-
-        // web_sys::console::log_1(&format!("Size of float is {:?}", FLOAT_SIZE).into());
-
-        Ok(4)
+        let quad = Mesh {
+            vertices: vec![-0.5, 0.5, 0., -0.5, -0.5, 0., 0.5, 0.5, 0., 0.5, -0.5, 0.],
+            uv: vec![0., 1., 0., 0., 1., 1., 1., 0.],
+            primitive: Primitive::TriangleStrip,
+            ..Default::default()
+        };
+        let quad = GpuMesh::new(gl, &quad).unwrap();
+        TextureProgram {
+            material,
+            quad: Node::with_mesh(Rc::new(quad)),
+        }
     }
 
+    /// Uploads image into a new texture on unit 0.
     pub fn init_texture(
         &self,
         gl: &WebGlRenderingContext,
         image: &HtmlImageElement,
-        count: u32,
-    ) -> Result<()> {
+    ) -> Result<WebGlTexture> {
         let texture = gl
             .create_texture()
             .ok_or_else(|| anyhow!("Failed to create texture"))?;
-        let sampler_uniform = gl
-            .get_uniform_location(&self.program, "u_Sampler")
-            .ok_or_else(|| anyhow!("Failed to get Sampler Uniform"))?;
 
         gl.pixel_storei(GL::UNPACK_FLIP_Y_WEBGL, 1);
         gl.active_texture(GL::TEXTURE0);
@@ -101,11 +72,17 @@ impl TextureProgram {
             ))
         })?;
 
-        gl.uniform1i(Some(&sampler_uniform), 0);
-        gl.clear(GL::COLOR_BUFFER_BIT);
-        gl.draw_arrays(GL::TRIANGLE_STRIP, 0, count as i32);
-
         web_sys::console::log_1(&"Finished texture mapping successfully".into());
-        Ok(())
+        Ok(texture)
+    }
+
+    /// Draws texture on a quad in middle of canvas.
+    pub fn render(&self, gl: &WebGlRenderingContext, texture: &WebGlTexture) {
+        gl.active_texture(GL::TEXTURE0);
+        gl.bind_texture(GL::TEXTURE_2D, Some(texture));
+        self.material.use_program(gl);
+        self.material.set(gl, "u_Sampler", &Uniform::Int(0));
+        gl.clear(GL::COLOR_BUFFER_BIT);
+        Renderer::new(Matrix4::identity()).render(gl, &self.material, &self.quad);
     }
 }
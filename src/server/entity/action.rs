@@ -1,22 +1,302 @@
-use super::color::Color;
+use super::{
+    color::Color,
+    encoding::Encoding,
+    error::ErrorCode,
+    token::{Status, Token},
+};
 use serde::{Deserialize, Serialize};
+
+/// Version of messages b/w client and server, bumped whenever they change in a way old clients can not follow.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Command as it is sent, along with an id its reply carries back.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct Request {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<u32>,
+    #[serde(flatten)]
+    pub command: Command,
+}
+
+/// Response as it is sent. Replies carry id of their request, events pushed by server do not.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct Reply {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<u32>,
+    #[serde(flatten)]
+    pub response: Response,
+}
+
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 #[serde(tag = "type")]
 pub enum Command {
-    CreateGame,
-    AvailableColors { id: u32 },
-    JoinGame { id: u32, color: Color },
-    StartGame { id: u32 },
+    /// First command on every connection, server refuses others until then.
+    /// Hello and its reply are json text, every message after them is in encoding client asked for.
+    Hello {
+        protocol_version: u32,
+        client_name: String,
+        #[serde(default)]
+        encoding: Encoding,
+    },
+    /// Game is timed with server's default time control, unless asked for another one
+    /// or for no clock at all.
+    CreateGame {
+        #[serde(default)]
+        time_control: Option<TimeControl>,
+        #[serde(default)]
+        untimed: bool,
+    },
+    AvailableColors {
+        id: u32,
+    },
+    /// Player may add a seed of its own to dice, before game starts.
+    JoinGame {
+        id: u32,
+        color: Color,
+        #[serde(default)]
+        client_seed: Option<String>,
+    },
+    /// Takes seat back with token player got on joining, like after server restarted.
+    /// Reply is a snapshot of game.
+    Rejoin {
+        id: u32,
+        color: Color,
+        rejoin_token: String,
+    },
+    StartGame {
+        id: u32,
+    },
+    RollDice {
+        id: u32,
+    },
+    MoveToken {
+        id: u32,
+        token_id: u8,
+    },
+    Snapshot {
+        id: u32,
+    },
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(tag = "type")]
 pub enum Response {
-    AvailableColols { colors: Vec<Color> },
-    // Select Color can fail, but to limit ping pong of message, if possible we will return alternative color.
-    PickedColor { color: Color },
-    CreateGameResponse { game_id: u32 },
-    FailureMessage { message: String },
+    Hello {
+        protocol_version: u32,
+        server_name: String,
+        encoding: Encoding,
+    },
+    AvailableColols {
+        colors: Vec<Color>,
+    },
+    // color player is seated with, joining fails with `ColorTaken` if someone else has it.
+    // token lets player rejoin over another connection, and is for its eyes only.
+    PickedColor {
+        color: Color,
+        rejoin_token: String,
+    },
+    CreateGameResponse {
+        game_id: u32,
+    },
+    Error {
+        code: ErrorCode,
+        message: String,
+    },
+    // Command was applied, what it changed is sent to every player as an event.
+    Accepted,
+    /// Pushed to every connection when server is about to stop. Games are saved,
+    /// and a restarted server is expected back within given milliseconds.
+    ServerShuttingDown {
+        reconnect_after: u64,
+    },
+    // Pushed to every player of game, without being asked for.
+    Event {
+        game_id: u32,
+        event: GameEvent,
+    },
+    Snapshot {
+        snapshot: GameSnapshot,
+    },
+}
+
+/// Something that happened in a game, sent to all its players.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type")]
+pub enum GameEvent {
+    PlayerJoined {
+        color: Color,
+    },
+    GameStarted {
+        players: Vec<Color>,
+    },
+    /// Hash of server's secret seed, and seeds players added, which all rolls of game come from.
+    DiceCommitted {
+        seed_hash: String,
+        client_seeds: Vec<String>,
+    },
+    /// Secret seed, once game is over, so that players can check every roll.
+    DiceRevealed {
+        server_seed: String,
+    },
+    DiceRolled {
+        color: Color,
+        dice: u8,
+    },
+    TokenMoved {
+        color: Color,
+        token_id: u8,
+        status: Status,
+    },
+    /// Sent after a roll which lets player move, listing every move it can make.
+    YourTurn {
+        color: Color,
+        dice: u8,
+        legal_moves: Vec<LegalMove>,
+    },
+    /// Rolled dice does not let player move, turn passes on to next player.
+    TurnPassed {
+        color: Color,
+        dice: u8,
+        reason: PassReason,
+    },
+    TurnChanged {
+        color: Color,
+    },
+    GameCompleted {
+        winner: Color,
+    },
+    /// Player ran out of time, and server plays its turn for it.
+    TurnTimedOut {
+        color: Color,
+    },
+    /// Too many turns in a row ran out of time, rest of turns of player are played quickly for it.
+    PlayerAway {
+        color: Color,
+    },
+    /// Away player acted on its own again.
+    PlayerReturned {
+        color: Color,
+    },
+    /// Player lost its connection before game started, and its color is free again.
+    PlayerLeft {
+        color: Color,
+    },
+    /// Player lost its connection during game, its turns are played quickly for it until it rejoins.
+    PlayerDisconnected {
+        color: Color,
+    },
+    /// Disconnected player has rejoined, and plays its own turns again.
+    PlayerReconnected {
+        color: Color,
+    },
+}
+
+/// Time players get to act, like a chess clock. Times are in milliseconds.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TimeControl {
+    /// For one turn, a roll along with its move.
+    pub per_turn: u64,
+    /// For all turns of a player together.
+    pub per_game: u64,
+    /// Turns in a row which run out of time, before player is away.
+    pub away_after: u8,
+}
+
+impl Default for TimeControl {
+    fn default() -> Self {
+        TimeControl {
+            per_turn: 30_000,
+            per_game: 15 * 60_000,
+            away_after: 3,
+        }
+    }
+}
+
+/// Game as it stands, for clients to draw without replaying its events. Times are in milliseconds.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GameSnapshot {
+    pub id: u32,
+    pub players: Vec<PlayerSnapshot>,
+    pub turn: Option<Color>,
+    pub dice: Option<u8>,
+    /// Hash of secret seed dice are rolled with.
+    pub seed_hash: String,
+    /// Seeds players added to dice.
+    #[serde(default)]
+    pub client_seeds: Vec<String>,
+    /// Index of next roll.
+    #[serde(default)]
+    pub roll_index: u32,
+    /// Every roll so far, oldest first, to check along with seeds once server reveals its own.
+    /// Shorter than `roll_index` only for games saved before rolls were kept.
+    #[serde(default)]
+    pub rolls: Vec<u8>,
+    /// Left of current turn, while a timed game is in progress.
+    pub turn_time_left: Option<u64>,
+}
+
+/// Game as listed for players looking for one to join.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GameSummary {
+    pub id: u32,
+    /// Like `ColorSelection`.
+    pub status: String,
+    /// Colors taken, in order they were joined.
+    pub colors: Vec<Color>,
+    /// Milliseconds since unix epoch.
+    pub created: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PlayerSnapshot {
+    pub color: Color,
+    pub tokens: Vec<Token>,
+    /// Left on clock of player for rest of a timed game.
+    pub time_left: Option<u64>,
+    pub away: bool,
+    #[serde(default)]
+    pub disconnected: bool,
+}
+
+/// A token which can be moved with rolled dice, and where it would end up.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LegalMove {
+    pub token_id: u8,
+    pub status: Status,
+    /// Tile of board as (x, z), with yellow home in cornor at 0, 0.
+    pub position: Option<(u8, u8)>,
+    /// Sends tokens of other colors standing there back home.
+    pub captures: bool,
+    /// Token can not be captured where it lands.
+    pub safe: bool,
+    pub finishes: bool,
+}
+
+/// Why a roll did not let player move.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type")]
+pub enum PassReason {
+    /// Every token still to play waits at home.
+    NeedsSix,
+    /// Every token on board is too close to finish for rolled dice.
+    Overshoots,
+}
+
+impl Command {
+    /// Name of command, same as its type on wire.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::Hello { .. } => "Hello",
+            Command::CreateGame { .. } => "CreateGame",
+            Command::AvailableColors { .. } => "AvailableColors",
+            Command::JoinGame { .. } => "JoinGame",
+            Command::Rejoin { .. } => "Rejoin",
+            Command::StartGame { .. } => "StartGame",
+            Command::RollDice { .. } => "RollDice",
+            Command::MoveToken { .. } => "MoveToken",
+            Command::Snapshot { .. } => "Snapshot",
+        }
+    }
 }
 
 impl Response {
@@ -33,11 +313,19 @@ impl Response {
     pub fn to_json(self) -> String {
         serde_json::to_string(&self).unwrap()
     }
+
+    /// Reply to request with given id.
+    pub fn reply_to(self, request_id: Option<u32>) -> Reply {
+        Reply {
+            request_id,
+            response: self,
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use super::Command;
+    use super::*;
 
     #[test]
     fn test_serde() {
@@ -48,4 +336,25 @@ mod test {
 
         assert_eq!(original_command, Command::AvailableColors { id: game_id });
     }
+
+    #[test]
+    fn test_request_id_sits_next_to_type() {
+        let request =
+            serde_json::from_str::<Request>(r#"{"type": "RollDice", "id": 3, "request_id": 7}"#)
+                .unwrap();
+        assert_eq!(request.request_id, Some(7));
+        assert_eq!(request.command, Command::RollDice { id: 3 });
+
+        let reply = Response::Error {
+            code: ErrorCode::NotYourTurn,
+            message: "Wait".to_owned(),
+        }
+        .reply_to(Some(7));
+        assert_eq!(
+            serde_json::to_value(&reply).unwrap(),
+            serde_json::json!({"type": "Error", "code": "NotYourTurn", "message": "Wait", "request_id": 7})
+        );
+        let event = serde_json::from_str::<Reply>(&Response::Accepted.to_json()).unwrap();
+        assert_eq!(event.request_id, None);
+    }
 }
@@ -0,0 +1,85 @@
+use rand::seq::SliceRandom;
+
+use super::token::{Status, Token};
+
+/// How hard computer opponents try.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    /// Moves any token it can.
+    Easy,
+    /// Keeps pushing token closest to home.
+    Normal,
+    /// Finishes tokens first, then gets new ones out, then runs token closest to home.
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "easy" => Some(Difficulty::Easy),
+            "normal" => Some(Difficulty::Normal),
+            "hard" => Some(Difficulty::Hard),
+            _ => None,
+        }
+    }
+}
+
+/// Id of token bot moves with rolled dice, None if no token can move.
+pub fn choose_token(tokens: &[Token], dice: u8, difficulty: Difficulty) -> Option<u8> {
+    let movable: Vec<&Token> = tokens
+        .iter()
+        .filter(|token| token.is_valid_move(dice))
+        .collect();
+    let chosen = match difficulty {
+        Difficulty::Easy => movable.choose(&mut rand::thread_rng()).cloned(),
+        Difficulty::Normal => movable.iter().max_by_key(|token| progress(token)).cloned(),
+        Difficulty::Hard => movable
+            .iter()
+            .max_by_key(|token| {
+                (
+                    finishes(token, dice),
+                    token.status() == &Status::Home,
+                    progress(token),
+                )
+            })
+            .cloned(),
+    };
+    chosen.map(|token| token.id())
+}
+
+// steps walked since leaving home.
+fn progress(token: &Token) -> u8 {
+    match token.status() {
+        Status::Home => 0,
+        Status::Running { pos } => *pos,
+        Status::FinalWalk { pos } => 51 + pos,
+        Status::Done => 57,
+    }
+}
+
+fn finishes(token: &Token, dice: u8) -> bool {
+    let mut moved = token.clone();
+    moved.move_token(dice).is_ok() && moved.status() == &Status::Done
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::color::Color;
+    use super::*;
+
+    #[test]
+    fn test_hard_bot_finishes_before_running() {
+        let tokens = [
+            Token::new(Color::Red, 1).with_staus(Status::Running { pos: 40 }),
+            Token::new(Color::Red, 2).with_staus(Status::FinalWalk { pos: 3 }),
+            Token::new(Color::Red, 3),
+            Token::new(Color::Red, 4).with_staus(Status::Done),
+        ];
+        assert_eq!(choose_token(&tokens, 3, Difficulty::Hard), Some(2));
+        assert_eq!(choose_token(&tokens, 6, Difficulty::Hard), Some(3));
+        assert_eq!(choose_token(&tokens, 6, Difficulty::Normal), Some(1));
+        assert_eq!(choose_token(&tokens[2..], 5, Difficulty::Easy), None);
+    }
+}
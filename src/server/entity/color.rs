@@ -13,6 +13,9 @@ pub enum Color {
 }
 
 impl Color {
+    /// All colors in clock wise order, which is also order of turns.
+    pub const ALL: [Color; 4] = [Color::Yellow, Color::Blue, Color::Red, Color::Green];
+
     /// As yellow is first quardent its offset is 0.
     /// All other color gets +13 offset for each clockwise distance from yellow.
     pub fn pos_offset(&self) -> u8 {
@@ -0,0 +1,77 @@
+use anyhow::{anyhow, Result};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+/// How messages are written on wire, picked by client in its hello.
+/// Json goes in text frames, MessagePack in binary frames.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Encoding {
+    #[default]
+    Json,
+    MessagePack,
+}
+
+impl Encoding {
+    pub fn encode<T: Serialize>(self, value: &T) -> Result<Vec<u8>> {
+        match self {
+            Encoding::Json => Ok(serde_json::to_vec(value)?),
+            // structs go as maps, as tags of messages can not be found in arrays.
+            Encoding::MessagePack => Ok(rmp_serde::to_vec_named(value)?),
+        }
+    }
+
+    pub fn decode<T: DeserializeOwned>(self, bytes: &[u8]) -> Result<T> {
+        match self {
+            Encoding::Json => {
+                serde_json::from_slice(bytes).map_err(|err| anyhow!("Failed to parse json {}", err))
+            }
+            Encoding::MessagePack => rmp_serde::from_slice(bytes)
+                .map_err(|err| anyhow!("Failed to parse message pack {}", err)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::server::entity::{
+        action::{Command, GameEvent, Reply, Request, Response},
+        color::Color,
+        error::ErrorCode,
+        token::Status,
+    };
+
+    #[rstest]
+    #[case(Encoding::Json)]
+    #[case(Encoding::MessagePack)]
+    fn test_messages_survive_encoding(#[case] encoding: Encoding) {
+        let request = Request {
+            request_id: Some(3),
+            command: Command::MoveToken { id: 1, token_id: 2 },
+        };
+        let bytes = encoding.encode(&request).unwrap();
+        assert_eq!(encoding.decode::<Request>(&bytes).unwrap(), request);
+
+        let replies = [
+            Response::Error {
+                code: ErrorCode::InvalidMove,
+                message: "No".to_owned(),
+            }
+            .reply_to(Some(3)),
+            Response::Event {
+                game_id: 1,
+                event: GameEvent::TokenMoved {
+                    color: Color::Red,
+                    token_id: 2,
+                    status: Status::Running { pos: 7 },
+                },
+            }
+            .reply_to(None),
+        ];
+        for reply in replies {
+            let bytes = encoding.encode(&reply).unwrap();
+            assert_eq!(encoding.decode::<Reply>(&bytes).unwrap(), reply);
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// What went wrong with a request, so that clients need not read messages to react to it.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ErrorCode {
+    /// Request is not a command this server understands.
+    BadJson,
+    /// Client speaks another version of protocol.
+    UnsupportedVersion,
+    /// Client has to say hello before anything else.
+    HelloFirst,
+    UnknownGame,
+    /// Command needs player to join game first.
+    NotJoined,
+    /// Every color is taken, or game has already started.
+    GameFull,
+    /// Asked color is taken by another player, others may still be free.
+    ColorTaken,
+    /// Game is not ready for command, like starting it without enough players.
+    NotReady,
+    NotYourTurn,
+    InvalidMove,
+    /// Anything else, which is a bug of server rather than of request.
+    Internal,
+}
+
+/// Failure which knows its code. Server reports failures without one as `Internal`.
+#[derive(Debug, Clone, Error, PartialEq)]
+#[error("{message}")]
+pub struct Failure {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl Failure {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Failure {
+            code,
+            message: message.into(),
+        }
+    }
+
+    /// Code of error, if it is a failure somewhere along its chain.
+    pub fn code_of(err: &anyhow::Error) -> ErrorCode {
+        err.chain()
+            .find_map(|cause| cause.downcast_ref::<Failure>())
+            .map_or(ErrorCode::Internal, |failure| failure.code)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use anyhow::{bail, Context, Result};
+
+    use super::*;
+
+    #[test]
+    fn test_code_survives_context() {
+        let failed = || -> Result<()> { bail!(Failure::new(ErrorCode::NotYourTurn, "Wait")) };
+        let err = failed().context("Failed to roll").unwrap_err();
+        assert_eq!(Failure::code_of(&err), ErrorCode::NotYourTurn);
+        assert_eq!(
+            Failure::code_of(&anyhow::anyhow!("Oops")),
+            ErrorCode::Internal
+        );
+    }
+}
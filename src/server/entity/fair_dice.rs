@@ -0,0 +1,166 @@
+use anyhow::{bail, Result};
+use hmac::{Hmac, KeyInit, Mac};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+// bytes of randomness in a server seed.
+const SEED_SIZE: usize = 32;
+// longest client seed accepted, in bytes.
+const MAX_CLIENT_SEED: usize = 64;
+
+/// Dice players can check once game is over. Server commits to a secret seed by publishing its hash,
+/// players add seeds of their own, and every roll is derived from all of them and its index.
+/// Secret seed is revealed when game completes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FairDice {
+    server_seed: String,
+    client_seeds: Vec<String>,
+    // rolls made so far, which is also index of next roll.
+    rolls: u32,
+    // dice of every roll in order, empty for games saved before it was kept.
+    #[serde(default)]
+    history: Vec<u8>,
+}
+
+impl Default for FairDice {
+    fn default() -> Self {
+        let mut seed = [0; SEED_SIZE];
+        rand::thread_rng().fill_bytes(&mut seed);
+        FairDice {
+            server_seed: hex::encode(seed),
+            client_seeds: Vec::new(),
+            rolls: 0,
+            history: Vec::new(),
+        }
+    }
+}
+
+impl FairDice {
+    /// Published before any roll, so that server can not change its seed later.
+    pub fn seed_hash(&self) -> String {
+        seed_hash(&self.server_seed)
+    }
+
+    /// Seed a client may add, which is checked on its own before it is added.
+    pub fn check_client_seed(seed: &str) -> Result<()> {
+        if seed.len() > MAX_CLIENT_SEED {
+            bail!("Client seed can be at most {} bytes", MAX_CLIENT_SEED);
+        }
+        Ok(())
+    }
+
+    pub fn add_client_seed(&mut self, seed: String) -> Result<()> {
+        FairDice::check_client_seed(&seed)?;
+        if self.rolls > 0 {
+            bail!("Seeds can not change once dice is rolled");
+        }
+        self.client_seeds.push(seed);
+        Ok(())
+    }
+
+    pub fn client_seeds(&self) -> &[String] {
+        &self.client_seeds
+    }
+
+    /// Dice next roll gets, without using it up.
+    pub fn next(&self) -> u8 {
+        dice_for(&self.server_seed, &self.client_seeds, self.rolls)
+    }
+
+    /// Moves on to next roll, once dice from `next` has been rolled.
+    pub fn rolled(&mut self, dice: u8) {
+        self.rolls += 1;
+        self.history.push(dice);
+    }
+
+    /// Index of next roll.
+    pub fn roll_index(&self) -> u32 {
+        self.rolls
+    }
+
+    /// Dice rolled so far, so that players who missed some of them can still check all of them.
+    pub fn history(&self) -> &[u8] {
+        &self.history
+    }
+
+    pub fn server_seed(&self) -> &str {
+        &self.server_seed
+    }
+}
+
+/// Hex encoded sha256 of seed.
+pub fn seed_hash(server_seed: &str) -> String {
+    hex::encode(Sha256::digest(server_seed.as_bytes()))
+}
+
+/// HMAC-SHA256 keyed with server seed, over every client seed prefixed by its length in 4 big endian bytes,
+/// followed by roll index in 4 big endian bytes. First byte below 252 of it picks dice, so that
+/// every face is equally likely.
+pub fn dice_for(server_seed: &str, client_seeds: &[String], roll_index: u32) -> u8 {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(server_seed.as_bytes()).expect("HMAC takes key of any size");
+    for seed in client_seeds {
+        mac.update(&(seed.len() as u32).to_be_bytes());
+        mac.update(seed.as_bytes());
+    }
+    mac.update(&roll_index.to_be_bytes());
+    let bytes = mac.finalize().into_bytes();
+    // all 32 bytes being 252 or more is too unlikely to matter, last of them is taken then.
+    let byte = bytes
+        .iter()
+        .find(|byte| **byte < 252)
+        .unwrap_or(&bytes[bytes.len() - 1]);
+    byte % 6 + 1
+}
+
+/// Checks revealed seed against hash published at start of game, and rolls of game in order they were made.
+pub fn verify(
+    published_hash: &str,
+    server_seed: &str,
+    client_seeds: &[String],
+    rolls: &[u8],
+) -> Result<()> {
+    if seed_hash(server_seed) != published_hash {
+        bail!("Revealed seed does not match hash published at start");
+    }
+    for (index, dice) in rolls.iter().enumerate() {
+        let expected = dice_for(server_seed, client_seeds, index as u32);
+        if expected != *dice {
+            bail!(
+                "Roll {} was {} but seeds give {}",
+                index + 1,
+                dice,
+                expected
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_rolls_verify_only_with_their_seeds() {
+        let mut dice = FairDice::default();
+        dice.add_client_seed("yellow".to_owned()).unwrap();
+        let rolls: Vec<u8> = (0..100)
+            .map(|_| {
+                let roll = dice.next();
+                dice.rolled(roll);
+                roll
+            })
+            .collect();
+        assert!(rolls.iter().all(|roll| (1..=6).contains(roll)));
+        assert_eq!(dice.history(), rolls.as_slice());
+        assert!(dice.add_client_seed("late".to_owned()).is_err());
+
+        let hash = dice.seed_hash();
+        let seeds = dice.client_seeds().to_vec();
+        assert!(verify(&hash, dice.server_seed(), &seeds, &rolls).is_ok());
+        assert!(verify(&hash, "guess", &seeds, &rolls).is_err());
+        assert!(verify(&hash, dice.server_seed(), &["red".to_owned()], &rolls).is_err());
+    }
+}
@@ -1,21 +1,64 @@
 use serde::{Deserialize, Serialize};
 
-use super::player::Player;
+use super::{
+    action::{
+        GameEvent, GameSnapshot, GameSummary, LegalMove, PassReason, PlayerSnapshot, TimeControl,
+    },
+    bot::{self, Difficulty},
+    color::Color,
+    error::{ErrorCode, Failure},
+    fair_dice::FairDice,
+    player::Player,
+    token::{Status as TokenStatus, Token},
+};
+use anyhow::{bail, Result};
+
+const MIN_PLAYERS: usize = 2;
+// milliseconds an away player's turn lasts, so that others can still follow it.
+const AWAY_TURN: u64 = 1_000;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Game {
     id: u32,
     status: Status,
+    // only timed games have one.
+    #[serde(default)]
+    clock: Option<Clock>,
+    #[serde(default)]
+    fair_dice: FairDice,
+    // milliseconds since unix epoch, zero for games saved before it was kept.
+    #[serde(default)]
+    created: u64,
+    // time as last told by `at`, in milliseconds.
+    #[serde(default)]
+    now: u64,
+    #[serde(default)]
+    turn_started: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Clock {
+    control: TimeControl,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 enum Status {
     Created,
-    ColorSelection { players: Vec<Player> },
-    InProgress { players: Vec<Player> },
+    ColorSelection {
+        players: Vec<Player>,
+    },
+    InProgress {
+        players: Vec<Player>,
+        // index of player whose turn it is.
+        turn: usize,
+        // rolled but not yet moved.
+        dice: Option<u8>,
+    },
     Abandoned,
-    Completed { players: Vec<Player> },
+    Completed {
+        players: Vec<Player>,
+    },
 }
 
 impl Game {
@@ -23,6 +66,891 @@ impl Game {
         Game {
             id,
             status: Status::Created,
+            clock: None,
+            fair_dice: FairDice::default(),
+            created: 0,
+            now: 0,
+            turn_started: 0,
+        }
+    }
+
+    /// Same game, created at given time in milliseconds.
+    pub fn created_at(self, created: u64) -> Game {
+        Game { created, ..self }
+    }
+
+    /// Game where every turn, and all turns of a player together, have to be played in time.
+    pub fn with_time_control(id: u32, control: TimeControl) -> Game {
+        Game {
+            clock: Some(Clock { control }),
+            ..Game::new(id)
+        }
+    }
+
+    /// Moves game to given time in milliseconds, before game is changed.
+    pub fn at(&mut self, now: u64) -> &mut Self {
+        self.now = now;
+        self
+    }
+
+    /// Current turn starts over, like when playing it failed.
+    pub fn restart_turn(&mut self) {
+        self.turn_started = self.now;
+    }
+
+    /// Carries on after server restarted, which no player is connected to yet.
+    /// Like any disconnect, seats of a game yet to start are freed. Players of a started game
+    /// keep their seats to rejoin, and current turn waits `grace` milliseconds longer for them.
+    pub fn resume(&mut self, grace: u64) {
+        let colors: Vec<Color> = self
+            .players()
+            .iter()
+            .map(|player| player.color().clone())
+            .collect();
+        for color in colors {
+            // no one is listening yet, so events are of no use.
+            self.disconnect(&color);
+        }
+        self.turn_started = self.now + grace;
+    }
+
+    /// Dice next roll has to use, for its rolls to be verified once game is over.
+    pub fn next_dice(&self) -> u8 {
+        self.fair_dice.next()
+    }
+
+    /// Joins like `join`, and player adds a seed of its own to dice, so that server alone can not decide rolls.
+    /// Every seated player adds at most one seed, and game is left as it was if either fails.
+    pub fn join_with_seed(
+        &mut self,
+        color: Color,
+        seed: Option<String>,
+    ) -> Result<(Color, Vec<GameEvent>)> {
+        let seed = match seed {
+            Some(seed) => seed,
+            None => return self.join(color),
+        };
+        FairDice::check_client_seed(&seed)?;
+        // players who left may have added seeds of their own.
+        if self.fair_dice.client_seeds().len() > self.players().len() {
+            bail!(Failure::new(
+                ErrorCode::GameFull,
+                format!("Game {} takes no more client seeds", self.id)
+            ));
+        }
+        let joined = self.join(color)?;
+        self.fair_dice.add_client_seed(seed)?;
+        Ok(joined)
+    }
+
+    /// When current player runs out of time, while game is in progress.
+    /// Players of an untimed game have no deadline, unless they are away.
+    pub fn turn_deadline(&self) -> Option<u64> {
+        let player = match &self.status {
+            Status::InProgress { players, turn, .. } => &players[*turn],
+            _ => return None,
+        };
+        let per_turn = if player.is_away() {
+            AWAY_TURN
+        } else {
+            self.clock.as_ref()?.control.per_turn
+        };
+        // only timed games keep time left of every player.
+        let time_left = self.clock.as_ref().map_or(per_turn, |_| player.time_left());
+        Some(self.turn_started + per_turn.min(time_left))
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Name of status game is in, like `InProgress`.
+    pub fn status_name(&self) -> &'static str {
+        match self.status {
+            Status::Created => "Created",
+            Status::ColorSelection { .. } => "ColorSelection",
+            Status::InProgress { .. } => "InProgress",
+            Status::Abandoned => "Abandoned",
+            Status::Completed { .. } => "Completed",
+        }
+    }
+
+    /// Whether game is yet to end, so that players may still look for it.
+    pub fn is_open(&self) -> bool {
+        !matches!(self.status, Status::Abandoned | Status::Completed { .. })
+    }
+
+    /// Color who has to act next, while game is in progress.
+    pub fn current_turn(&self) -> Option<&Color> {
+        match &self.status {
+            Status::InProgress { players, turn, .. } => Some(players[*turn].color()),
+            _ => None,
+        }
+    }
+
+    /// Dice rolled by current player, which is yet to be moved.
+    pub fn pending_dice(&self) -> Option<u8> {
+        match &self.status {
+            Status::InProgress { dice, .. } => *dice,
+            _ => None,
+        }
+    }
+
+    pub fn tokens(&self, color: &Color) -> Option<&[Token; 4]> {
+        self.players()
+            .iter()
+            .find(|player| player.color() == color)
+            .map(|player| player.tokens())
+    }
+
+    /// Game as lobby lists it.
+    pub fn summary(&self) -> GameSummary {
+        GameSummary {
+            id: self.id,
+            status: self.status_name().to_owned(),
+            colors: self
+                .players()
+                .iter()
+                .map(|player| player.color().clone())
+                .collect(),
+            created: self.created,
+        }
+    }
+
+    pub fn snapshot(&self) -> GameSnapshot {
+        let now = self.now;
+        let turn = self.current_turn().cloned();
+        let players = self
+            .players()
+            .iter()
+            .map(|player| {
+                // current player's clock is running, rest are stopped.
+                let running = match (&self.clock, &turn) {
+                    (Some(_), Some(turn)) if turn == player.color() => {
+                        now.saturating_sub(self.turn_started)
+                    }
+                    _ => 0,
+                };
+                PlayerSnapshot {
+                    color: player.color().clone(),
+                    tokens: player.tokens().to_vec(),
+                    time_left: self
+                        .clock
+                        .as_ref()
+                        .map(|_| player.time_left().saturating_sub(running)),
+                    away: player.is_away(),
+                    disconnected: player.is_disconnected(),
+                }
+            })
+            .collect();
+        GameSnapshot {
+            id: self.id,
+            players,
+            turn,
+            dice: self.pending_dice(),
+            seed_hash: self.fair_dice.seed_hash(),
+            client_seeds: self.fair_dice.client_seeds().to_vec(),
+            roll_index: self.fair_dice.roll_index(),
+            rolls: self.fair_dice.history().to_vec(),
+            turn_time_left: self
+                .turn_deadline()
+                .map(|deadline| deadline.saturating_sub(now)),
+        }
+    }
+
+    /// Colors nobody has picked yet. Empty once game has started.
+    pub fn available_colors(&self) -> Vec<Color> {
+        match &self.status {
+            Status::Created => Color::ALL.to_vec(),
+            Status::ColorSelection { players } => Color::ALL
+                .iter()
+                .filter(|color| players.iter().all(|player| player.color() != *color))
+                .cloned()
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn join(&mut self, color: Color) -> Result<(Color, Vec<GameEvent>)> {
+        let available = self.available_colors();
+        if available.is_empty() {
+            bail!(Failure::new(
+                ErrorCode::GameFull,
+                format!("Game {} can not be joined", self.id)
+            ));
+        }
+        if !available.contains(&color) {
+            bail!(Failure::new(
+                ErrorCode::ColorTaken,
+                format!("{:?} is taken in game {}", color, self.id)
+            ));
+        }
+
+        let player = Player::new(color.clone(), format!("{:?}", color));
+        match &mut self.status {
+            Status::Created => {
+                self.status = Status::ColorSelection {
+                    players: vec![player],
+                }
+            }
+            Status::ColorSelection { players } => players.push(player),
+            _ => bail!(Failure::new(
+                ErrorCode::GameFull,
+                format!("Game {} can not be joined", self.id)
+            )),
+        }
+        Ok((color.clone(), vec![GameEvent::PlayerJoined { color }]))
+    }
+
+    /// Token player can rejoin with, while it is seated.
+    pub fn rejoin_token(&self, color: &Color) -> Option<&str> {
+        self.players()
+            .iter()
+            .find(|player| player.color() == color)
+            .map(|player| player.rejoin_token())
+    }
+
+    /// Another connection plays as player, given token it got on joining.
+    pub fn rejoin(&mut self, color: &Color, rejoin_token: &str) -> Result<Vec<GameEvent>> {
+        let id = self.id;
+        let player = self
+            .player_mut(color)
+            .filter(|player| player.accepts(rejoin_token))
+            .ok_or_else(|| {
+                Failure::new(
+                    ErrorCode::NotJoined,
+                    format!("{:?} has no seat in game {} for this token", color, id),
+                )
+            })?;
+        if !player.reconnect() {
+            return Ok(Vec::new());
+        }
+        Ok(vec![GameEvent::PlayerReconnected {
+            color: color.clone(),
+        }])
+    }
+
+    /// Player has lost a connection, and is gone once it has lost all of them.
+    /// Its seat is freed before game starts, afterwards it stays away
+    /// and its turns are played for it until it rejoins.
+    pub fn disconnect(&mut self, color: &Color) -> Vec<GameEvent> {
+        match &mut self.status {
+            Status::ColorSelection { players } => {
+                let gone = players
+                    .iter_mut()
+                    .any(|player| player.color() == color && player.disconnect());
+                if !gone {
+                    return Vec::new();
+                }
+                players.retain(|player| player.color() != color);
+                if players.is_empty() {
+                    self.status = Status::Created;
+                }
+                vec![GameEvent::PlayerLeft {
+                    color: color.clone(),
+                }]
+            }
+            Status::InProgress { .. } => {
+                if !self
+                    .player_mut(color)
+                    .is_some_and(|player| player.disconnect())
+                {
+                    return Vec::new();
+                }
+                vec![GameEvent::PlayerDisconnected {
+                    color: color.clone(),
+                }]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn start(&mut self) -> Result<Vec<GameEvent>> {
+        let mut players = match &mut self.status {
+            Status::ColorSelection { players } if players.len() >= MIN_PLAYERS => {
+                std::mem::take(players)
+            }
+            _ => bail!(Failure::new(
+                ErrorCode::NotReady,
+                format!(
+                    "Game {} needs at least {} players to start",
+                    self.id, MIN_PLAYERS
+                )
+            )),
+        };
+        // turns go clock wise, no matter who joined first.
+        players.sort_by_key(|player| Color::ALL.iter().position(|color| color == player.color()));
+        let colors: Vec<Color> = players
+            .iter()
+            .map(|player| player.color().clone())
+            .collect();
+        self.turn_started = self.now;
+        if let Some(clock) = &self.clock {
+            for player in players.iter_mut() {
+                player.set_time_left(clock.control.per_game);
+            }
+        }
+        self.status = Status::InProgress {
+            players,
+            turn: 0,
+            dice: None,
+        };
+        Ok(vec![
+            GameEvent::DiceCommitted {
+                seed_hash: self.fair_dice.seed_hash(),
+                client_seeds: self.fair_dice.client_seeds().to_vec(),
+            },
+            GameEvent::GameStarted {
+                players: colors.clone(),
+            },
+            GameEvent::TurnChanged {
+                color: colors[0].clone(),
+            },
+        ])
+    }
+
+    /// Player learns every move rolled dice allows. If there is none, turn passes on right away.
+    pub fn roll(&mut self, color: &Color, dice: u8) -> Result<Vec<GameEvent>> {
+        let events = self.roll_for(color, dice)?;
+        Ok(self.acted(color, events))
+    }
+
+    /// Tokens of other colors where token lands go back home, unless it lands on a safe cell.
+    /// Six earns another roll, first player to bring all tokens home wins.
+    pub fn move_token(&mut self, color: &Color, token_id: u8) -> Result<Vec<GameEvent>> {
+        let events = self.move_for(color, token_id)?;
+        Ok(self.acted(color, events))
+    }
+
+    /// Plays turn of current player, whose time has run out, like a bot would.
+    /// dice is used if player has not rolled yet.
+    pub fn time_out(&mut self, dice: u8) -> Result<Vec<GameEvent>> {
+        let away_after = self.clock.as_ref().map(|clock| clock.control.away_after);
+        let color = self.current_turn().cloned().ok_or_else(|| {
+            Failure::new(
+                ErrorCode::NotReady,
+                format!("Game {} is not in progress", self.id),
+            )
+        })?;
+        let mut events = vec![GameEvent::TurnTimedOut {
+            color: color.clone(),
+        }];
+        // only timed games make players away, players of untimed ones time out once they are away.
+        if away_after.is_some_and(|away_after| {
+            self.player_mut(&color)
+                .is_some_and(|player| player.time_out(away_after))
+        }) {
+            events.push(GameEvent::PlayerAway {
+                color: color.clone(),
+            });
+        }
+
+        if self.pending_dice().is_none() {
+            events.extend(self.roll_for(&color, dice)?);
+        }
+        if let Some(dice) = self.pending_dice() {
+            let token_id = self
+                .tokens(&color)
+                .and_then(|tokens| bot::choose_token(tokens, dice, Difficulty::Normal))
+                .ok_or_else(|| {
+                    Failure::new(
+                        ErrorCode::InvalidMove,
+                        format!("{:?} has no token to move", color),
+                    )
+                })?;
+            events.extend(self.move_for(&color, token_id)?);
+        }
+        Ok(events)
+    }
+
+    fn roll_for(&mut self, color: &Color, dice: u8) -> Result<Vec<GameEvent>> {
+        let id = self.id;
+        let (players, turn, pending) = self.turn_of(color)?;
+        if pending.is_some() {
+            bail!(Failure::new(
+                ErrorCode::InvalidMove,
+                format!("Game {} is waiting for {:?} to move", id, color)
+            ));
         }
+
+        let mut events = vec![GameEvent::DiceRolled {
+            color: color.clone(),
+            dice,
+        }];
+        let legal_moves = legal_moves(players, &players[*turn], dice);
+        if legal_moves.is_empty() {
+            let on_board = players[*turn].tokens().iter().any(|token| {
+                matches!(
+                    token.status(),
+                    TokenStatus::Running { .. } | TokenStatus::FinalWalk { .. }
+                )
+            });
+            events.push(GameEvent::TurnPassed {
+                color: color.clone(),
+                dice,
+                reason: if on_board {
+                    PassReason::Overshoots
+                } else {
+                    PassReason::NeedsSix
+                },
+            });
+            *turn = (*turn + 1) % players.len();
+            events.push(GameEvent::TurnChanged {
+                color: players[*turn].color().clone(),
+            });
+        } else {
+            *pending = Some(dice);
+            events.push(GameEvent::YourTurn {
+                color: color.clone(),
+                dice,
+                legal_moves,
+            });
+        }
+        self.fair_dice.rolled(dice);
+        self.end_turn(color, &events);
+        Ok(events)
+    }
+
+    fn move_for(&mut self, color: &Color, token_id: u8) -> Result<Vec<GameEvent>> {
+        let id = self.id;
+        let (players, turn, pending) = self.turn_of(color)?;
+        let dice = pending.ok_or_else(|| {
+            Failure::new(
+                ErrorCode::InvalidMove,
+                format!("Game {} is waiting for a roll", id),
+            )
+        })?;
+        let token = players[*turn].token_mut(token_id).ok_or_else(|| {
+            Failure::new(
+                ErrorCode::InvalidMove,
+                format!("There is no token {}", token_id),
+            )
+        })?;
+        token.move_token(dice)?;
+        let moved = token.clone();
+        let mut events = vec![GameEvent::TokenMoved {
+            color: color.clone(),
+            token_id,
+            status: moved.status().clone(),
+        }];
+        *pending = None;
+
+        for (captured_color, captured_id) in captured_by(players, &moved) {
+            let captured = players
+                .iter_mut()
+                .find(|player| player.color() == &captured_color)
+                .and_then(|player| player.token_mut(captured_id));
+            if let Some(captured) = captured {
+                *captured = captured.clone().with_staus(TokenStatus::Home);
+                events.push(GameEvent::TokenMoved {
+                    color: captured_color,
+                    token_id: captured_id,
+                    status: TokenStatus::Home,
+                });
+            }
+        }
+
+        if players[*turn].is_done() {
+            let players = std::mem::take(players);
+            self.status = Status::Completed { players };
+            events.push(GameEvent::GameCompleted {
+                winner: color.clone(),
+            });
+            events.push(GameEvent::DiceRevealed {
+                server_seed: self.fair_dice.server_seed().to_owned(),
+            });
+            return Ok(events);
+        }
+        if dice != 6 {
+            *turn = (*turn + 1) % players.len();
+        }
+        events.push(GameEvent::TurnChanged {
+            color: players[*turn].color().clone(),
+        });
+        self.end_turn(color, &events);
+        Ok(events)
+    }
+
+    // player is no longer away once it acts by itself.
+    fn acted(&mut self, color: &Color, mut events: Vec<GameEvent>) -> Vec<GameEvent> {
+        if self.player_mut(color).is_some_and(|player| player.act()) {
+            events.insert(
+                0,
+                GameEvent::PlayerReturned {
+                    color: color.clone(),
+                },
+            );
+        }
+        events
+    }
+
+    // once turn changes, time it took comes off clock of player who played it, and next turn starts.
+    fn end_turn(&mut self, color: &Color, events: &[GameEvent]) {
+        let turn_changed = events
+            .iter()
+            .any(|event| matches!(event, GameEvent::TurnChanged { .. }));
+        if !turn_changed {
+            return;
+        }
+        let took = self.now.saturating_sub(self.turn_started);
+        if self.clock.is_some() {
+            if let Some(player) = self.player_mut(color) {
+                player.set_time_left(player.time_left().saturating_sub(took));
+            }
+        }
+        self.turn_started = self.now;
+    }
+
+    fn players(&self) -> &[Player] {
+        match &self.status {
+            Status::ColorSelection { players }
+            | Status::InProgress { players, .. }
+            | Status::Completed { players } => players,
+            _ => &[],
+        }
+    }
+
+    fn player_mut(&mut self, color: &Color) -> Option<&mut Player> {
+        match &mut self.status {
+            Status::ColorSelection { players }
+            | Status::InProgress { players, .. }
+            | Status::Completed { players } => {
+                players.iter_mut().find(|player| player.color() == color)
+            }
+            _ => None,
+        }
+    }
+
+    fn turn_of(
+        &mut self,
+        color: &Color,
+    ) -> Result<(&mut Vec<Player>, &mut usize, &mut Option<u8>)> {
+        match &mut self.status {
+            Status::InProgress {
+                players,
+                turn,
+                dice,
+            } => {
+                if players[*turn].color() != color {
+                    bail!(Failure::new(
+                        ErrorCode::NotYourTurn,
+                        format!("It is not turn of {:?}", color)
+                    ));
+                }
+                Ok((players, turn, dice))
+            }
+            _ => bail!(Failure::new(
+                ErrorCode::NotReady,
+                format!("Game {} is not in progress", self.id)
+            )),
+        }
+    }
+}
+
+// every move player can make with dice, and what each of them leads to.
+fn legal_moves(players: &[Player], player: &Player, dice: u8) -> Vec<LegalMove> {
+    player
+        .tokens()
+        .iter()
+        .filter_map(|token| {
+            let mut moved = token.clone();
+            moved.move_token(dice).ok()?;
+            Some(LegalMove {
+                token_id: token.id(),
+                status: moved.status().clone(),
+                position: moved.get_x_z(),
+                captures: !captured_by(players, &moved).is_empty(),
+                safe: moved.is_safe(),
+                finishes: moved.status() == &TokenStatus::Done,
+            })
+        })
+        .collect()
+}
+
+// tokens of other colors, as (color, id), sharing cell of token which just landed there.
+fn captured_by(players: &[Player], token: &Token) -> Vec<(Color, u8)> {
+    let track_pos = match token.track_pos() {
+        Some(track_pos) if !token.is_safe() => track_pos,
+        _ => return Vec::new(),
+    };
+    players
+        .iter()
+        .filter(|player| player.color() != token.color())
+        .flat_map(|player| player.tokens().iter())
+        .filter(|other| other.track_pos() == Some(track_pos))
+        .map(|other| (other.color().clone(), other.id()))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn started_game() -> Game {
+        let mut game = Game::new(1);
+        game.join(Color::Red).unwrap();
+        game.join(Color::Yellow).unwrap();
+        game.start().unwrap();
+        game
+    }
+
+    #[test]
+    fn test_taken_color_is_refused() {
+        let mut game = Game::new(1);
+        assert_eq!(game.join(Color::Blue).unwrap().0, Color::Blue);
+        let taken = game.join(Color::Blue).unwrap_err();
+        assert_eq!(Failure::code_of(&taken), ErrorCode::ColorTaken);
+        assert_eq!(game.join(Color::Yellow).unwrap().0, Color::Yellow);
+        assert_eq!(game.available_colors(), vec![Color::Red, Color::Green]);
+    }
+
+    #[test]
+    fn test_start_needs_two_players() {
+        let mut game = Game::new(1);
+        game.join(Color::Blue).unwrap();
+        assert!(game.start().is_err());
+        game.join(Color::Green).unwrap();
+        assert!(game.start().is_ok());
+        assert!(game.join(Color::Red).is_err());
+    }
+
+    #[test]
+    fn test_turn_passes_without_valid_move() {
+        let mut game = started_game();
+        // yellow comes before red, even though red joined first.
+        assert!(game.roll(&Color::Red, 3).is_err());
+        assert_eq!(
+            game.roll(&Color::Yellow, 3).unwrap(),
+            vec![
+                GameEvent::DiceRolled {
+                    color: Color::Yellow,
+                    dice: 3
+                },
+                GameEvent::TurnPassed {
+                    color: Color::Yellow,
+                    dice: 3,
+                    reason: PassReason::NeedsSix,
+                },
+                GameEvent::TurnChanged { color: Color::Red },
+            ]
+        );
+        let snapshot = game.snapshot();
+        assert_eq!((snapshot.roll_index, snapshot.rolls), (1, vec![3]));
+    }
+
+    #[test]
+    fn test_six_earns_another_roll() {
+        let mut game = started_game();
+        game.roll(&Color::Yellow, 6).unwrap();
+        assert!(game.roll(&Color::Yellow, 6).is_err());
+        let events = game.move_token(&Color::Yellow, 1).unwrap();
+        assert_eq!(
+            events.last(),
+            Some(&GameEvent::TurnChanged {
+                color: Color::Yellow
+            })
+        );
+        assert!(game.move_token(&Color::Yellow, 1).is_err());
+    }
+
+    fn place(game: &mut Game, color: Color, token_id: u8, status: TokenStatus) {
+        if let Status::InProgress { players, .. } = &mut game.status {
+            let player = players.iter_mut().find(|player| player.color() == &color);
+            let token = player
+                .and_then(|player| player.token_mut(token_id))
+                .unwrap();
+            *token = token.clone().with_staus(status);
+        }
+    }
+
+    #[test]
+    fn test_legal_moves_and_capture() {
+        let mut game = started_game();
+        place(&mut game, Color::Yellow, 1, TokenStatus::Running { pos: 3 });
+        place(&mut game, Color::Yellow, 2, TokenStatus::Running { pos: 7 });
+        // red starts 26 cells after yellow, so these stand 5 and 9 cells after start of yellow.
+        place(&mut game, Color::Red, 1, TokenStatus::Running { pos: 31 });
+        place(&mut game, Color::Red, 2, TokenStatus::Running { pos: 35 });
+
+        let events = game.roll(&Color::Yellow, 2).unwrap();
+        let legal_moves = match events.last() {
+            Some(GameEvent::YourTurn { legal_moves, .. }) => legal_moves.clone(),
+            event => panic!("Expected legal moves, got {:?}", event),
+        };
+        assert_eq!(
+            legal_moves
+                .iter()
+                .map(|legal| (legal.token_id, legal.captures, legal.safe, legal.finishes))
+                .collect::<Vec<_>>(),
+            vec![(1, true, false, false), (2, false, true, false)]
+        );
+        assert_eq!(legal_moves[0].status, TokenStatus::Running { pos: 5 });
+        assert_eq!(legal_moves[0].position, Some((6, 5)));
+
+        let events = game.move_token(&Color::Yellow, 1).unwrap();
+        assert_eq!(
+            events[1],
+            GameEvent::TokenMoved {
+                color: Color::Red,
+                token_id: 1,
+                status: TokenStatus::Home,
+            }
+        );
+        assert_eq!(
+            game.tokens(&Color::Red).unwrap()[1].status(),
+            &TokenStatus::Running { pos: 35 }
+        );
+    }
+
+    #[test]
+    fn test_seeds_are_added_only_by_seated_players() {
+        let mut game = Game::new(1);
+        for color in Color::ALL {
+            game.join_with_seed(color, Some("seed".to_owned())).unwrap();
+        }
+        assert!(game
+            .join_with_seed(Color::Red, Some("late".to_owned()))
+            .is_err());
+        assert_eq!(game.fair_dice.client_seeds().len(), 4);
+
+        // seed of a player who left still counts.
+        let mut game = Game::new(2);
+        game.join_with_seed(Color::Red, Some("first".to_owned()))
+            .unwrap();
+        game.disconnect(&Color::Red);
+        assert!(game
+            .join_with_seed(Color::Red, Some("again".to_owned()))
+            .is_err());
+        assert!(game.available_colors().contains(&Color::Red));
+        game.join_with_seed(Color::Red, None).unwrap();
+        assert_eq!(game.fair_dice.client_seeds().len(), 1);
+    }
+
+    #[test]
+    fn test_disconnect_frees_seat_until_game_starts() {
+        let mut game = Game::new(1);
+        game.join(Color::Yellow).unwrap();
+        game.join(Color::Red).unwrap();
+        assert_eq!(
+            game.disconnect(&Color::Red),
+            vec![GameEvent::PlayerLeft { color: Color::Red }]
+        );
+        assert!(game.available_colors().contains(&Color::Red));
+        assert!(game.start().is_err());
+
+        game.join(Color::Red).unwrap();
+        game.start().unwrap();
+        assert_eq!(
+            game.disconnect(&Color::Red),
+            vec![GameEvent::PlayerDisconnected { color: Color::Red }]
+        );
+        assert!(game.disconnect(&Color::Red).is_empty());
+        let snapshot = game.snapshot();
+        assert!(snapshot.players[1].away && snapshot.players[1].disconnected);
+    }
+
+    #[test]
+    fn test_resume_keeps_seats_only_of_started_game() {
+        let restored = |game: &Game| -> Game {
+            let mut game: Game =
+                serde_json::from_str(&serde_json::to_string(game).unwrap()).unwrap();
+            game.resume(1_000);
+            game
+        };
+        let mut waiting = Game::new(1);
+        waiting.join(Color::Yellow).unwrap();
+        let waiting = restored(&waiting);
+        assert_eq!(waiting.status_name(), "Created");
+        assert_eq!(waiting.available_colors(), Color::ALL.to_vec());
+
+        let started = restored(&started_game());
+        assert_eq!(started.status_name(), "InProgress");
+        assert!(started
+            .snapshot()
+            .players
+            .iter()
+            .all(|player| player.away && player.disconnected));
+    }
+
+    #[test]
+    fn test_rejoined_player_outlives_its_old_connection() {
+        let mut game = started_game();
+        let rejoin_token = game.rejoin_token(&Color::Red).unwrap().to_owned();
+        assert!(game.rejoin(&Color::Red, "guess").is_err());
+        assert!(game.rejoin(&Color::Yellow, &rejoin_token).is_err());
+
+        // new connection rejoins before old one is found gone.
+        assert!(game.rejoin(&Color::Red, &rejoin_token).unwrap().is_empty());
+        assert!(game.disconnect(&Color::Red).is_empty());
+        assert_eq!(
+            game.disconnect(&Color::Red),
+            vec![GameEvent::PlayerDisconnected { color: Color::Red }]
+        );
+        assert_eq!(
+            game.rejoin(&Color::Red, &rejoin_token).unwrap(),
+            vec![GameEvent::PlayerReconnected { color: Color::Red }]
+        );
+        assert!(game.snapshot().players.iter().all(|player| !player.away));
+    }
+
+    #[test]
+    fn test_disconnected_player_does_not_stall_untimed_game() {
+        let mut game = Game::new(1);
+        game.join(Color::Yellow).unwrap();
+        game.join(Color::Red).unwrap();
+        game.at(0).start().unwrap();
+        assert_eq!(game.turn_deadline(), None);
+
+        game.at(500).disconnect(&Color::Yellow);
+        assert_eq!(game.turn_deadline(), Some(AWAY_TURN));
+        let events = game.at(AWAY_TURN).time_out(3).unwrap();
+        assert!(events.contains(&GameEvent::DiceRolled {
+            color: Color::Yellow,
+            dice: 3
+        }));
+        assert_eq!(game.current_turn(), Some(&Color::Red));
+        assert_eq!(game.turn_deadline(), None);
+    }
+
+    #[test]
+    fn test_timed_out_turns_make_player_away() {
+        let control = TimeControl {
+            per_turn: 1_000,
+            per_game: 10_000,
+            away_after: 2,
+        };
+        let mut game = Game::with_time_control(1, control);
+        game.join(Color::Yellow).unwrap();
+        game.join(Color::Red).unwrap();
+        game.at(0).start().unwrap();
+        assert_eq!(game.turn_deadline(), Some(1_000));
+
+        let events = game.at(1_000).time_out(3).unwrap();
+        assert_eq!(
+            events[0],
+            GameEvent::TurnTimedOut {
+                color: Color::Yellow
+            }
+        );
+        assert_eq!(game.current_turn(), Some(&Color::Red));
+        let snapshot = game.at(1_500).snapshot();
+        assert_eq!(snapshot.turn_time_left, Some(500));
+        assert_eq!(snapshot.players[0].time_left, Some(9_000));
+        assert_eq!(snapshot.players[1].time_left, Some(9_500));
+
+        game.roll(&Color::Red, 3).unwrap();
+        // six is moved for yellow, which then gets a short turn to roll again.
+        let events = game.at(2_500).time_out(6).unwrap();
+        assert!(events.contains(&GameEvent::PlayerAway {
+            color: Color::Yellow
+        }));
+        assert_eq!(game.turn_deadline(), Some(3_500));
+        let events = game.at(3_000).roll(&Color::Yellow, 2).unwrap();
+        assert_eq!(
+            events[0],
+            GameEvent::PlayerReturned {
+                color: Color::Yellow
+            }
+        );
     }
 }
@@ -1,5 +1,9 @@
-pub(super) mod action;
+pub(crate) mod action;
+pub mod bot;
 pub mod color;
+pub mod encoding;
+pub mod error;
+pub mod fair_dice;
 pub mod game;
 pub mod player;
 pub mod token;
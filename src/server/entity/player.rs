@@ -1,9 +1,138 @@
+use rand::RngCore;
 use serde::{Deserialize, Serialize};
 
-use super::token::Token;
+use super::{
+    color::Color,
+    token::{Status, Token},
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub(super) struct Player {
+pub(crate) struct Player {
+    color: Color,
     tokens: [Token; 4],
     name: String,
+    // milliseconds left on clock of a timed game.
+    #[serde(default)]
+    time_left: u64,
+    // turns in a row which ran out of time.
+    #[serde(default)]
+    timeouts: u8,
+    #[serde(default)]
+    away: bool,
+    // lost every connection, which also leaves it away until it rejoins.
+    #[serde(default)]
+    disconnected: bool,
+    // lets player take its seat back over another connection, empty for players saved before it was kept.
+    #[serde(default)]
+    rejoin_token: String,
+    // connections playing as player, which do not outlive server.
+    #[serde(skip)]
+    connections: u32,
+}
+
+impl Player {
+    pub fn new(color: Color, name: impl Into<String>) -> Self {
+        let tokens = [1, 2, 3, 4].map(|id| Token::new(color.clone(), id));
+        Player {
+            color,
+            tokens,
+            name: name.into(),
+            time_left: 0,
+            timeouts: 0,
+            away: false,
+            disconnected: false,
+            rejoin_token: new_rejoin_token(),
+            connections: 1,
+        }
+    }
+
+    pub fn color(&self) -> &Color {
+        &self.color
+    }
+
+    pub fn tokens(&self) -> &[Token; 4] {
+        &self.tokens
+    }
+
+    pub fn token_mut(&mut self, id: u8) -> Option<&mut Token> {
+        self.tokens.iter_mut().find(|token| token.id() == id)
+    }
+
+    pub fn time_left(&self) -> u64 {
+        self.time_left
+    }
+
+    pub fn set_time_left(&mut self, time_left: u64) {
+        self.time_left = time_left;
+    }
+
+    pub fn is_away(&self) -> bool {
+        self.away
+    }
+
+    /// Counts a turn played for player because it ran out of time.
+    /// Returns true if it made player away.
+    pub fn time_out(&mut self, away_after: u8) -> bool {
+        self.timeouts = self.timeouts.saturating_add(1);
+        let went_away = !self.away && self.timeouts >= away_after;
+        self.away |= went_away;
+        went_away
+    }
+
+    pub fn is_disconnected(&self) -> bool {
+        self.disconnected
+    }
+
+    pub fn rejoin_token(&self) -> &str {
+        &self.rejoin_token
+    }
+
+    /// Whether token lets a connection play as this player.
+    pub fn accepts(&self, rejoin_token: &str) -> bool {
+        !self.rejoin_token.is_empty() && self.rejoin_token == rejoin_token
+    }
+
+    /// One of connections playing as player is gone.
+    /// Returns true if it was last one, which leaves player disconnected.
+    pub fn disconnect(&mut self) -> bool {
+        self.connections = self.connections.saturating_sub(1);
+        if self.connections > 0 || self.disconnected {
+            return false;
+        }
+        self.disconnected = true;
+        self.away = true;
+        true
+    }
+
+    /// Another connection plays as player. Returns true if player was disconnected until now.
+    pub fn reconnect(&mut self) -> bool {
+        self.connections += 1;
+        if !self.disconnected {
+            return false;
+        }
+        self.disconnected = false;
+        self.away = false;
+        self.timeouts = 0;
+        true
+    }
+
+    /// Player acted on its own. Returns true if it was away until now.
+    pub fn act(&mut self) -> bool {
+        self.timeouts = 0;
+        std::mem::replace(&mut self.away, false)
+    }
+
+    /// All tokens have reached home.
+    pub fn is_done(&self) -> bool {
+        self.tokens
+            .iter()
+            .all(|token| *token.status() == Status::Done)
+    }
+}
+
+// hex of 16 random bytes, too many to guess.
+fn new_rejoin_token() -> String {
+    let mut token = [0; 16];
+    rand::thread_rng().fill_bytes(&mut token);
+    hex::encode(token)
 }
@@ -1,8 +1,13 @@
 use serde::{Deserialize, Serialize};
 
 use super::color::Color;
+use super::error::{ErrorCode, Failure};
 use anyhow::{bail, Result};
 
+/// Cells of common track where tokens can not be captured, as positions counted from start of yellow.
+/// Every color starts on one, and there is a star 8 cells after each start.
+const SAFE_CELLS: [u8; 8] = [1, 9, 14, 22, 27, 35, 40, 48];
+
 /// There are 4 token of each color. Their ids are b/w 1 - 4.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Token {
@@ -38,7 +43,19 @@ impl Token {
         }
     }
 
-    fn with_staus(self, status: Status) -> Self {
+    pub fn color(&self) -> &Color {
+        &self.color
+    }
+
+    pub fn id(&self) -> u8 {
+        self.id
+    }
+
+    pub fn status(&self) -> &Status {
+        &self.status
+    }
+
+    pub fn with_staus(self, status: Status) -> Self {
         Token {
             color: self.color,
             id: self.id,
@@ -60,6 +77,23 @@ impl Token {
     //     }
     // }
 
+    /// Position on common track counted from start of yellow, so that tokens of all colors can be compared.
+    /// None when token is not on common track.
+    pub fn track_pos(&self) -> Option<u8> {
+        match self.status {
+            Status::Running { pos } => Some((pos + self.color.pos_offset()) % 52),
+            _ => None,
+        }
+    }
+
+    /// Token can not be captured where it stands, on a safe cell or once it has left common track.
+    pub fn is_safe(&self) -> bool {
+        match self.track_pos() {
+            Some(track_pos) => SAFE_CELLS.contains(&track_pos),
+            None => self.status != Status::Home,
+        }
+    }
+
     pub fn is_valid_move(&self, count: u8) -> bool {
         match self.status {
             Status::Home => count == 6,
@@ -71,7 +105,10 @@ impl Token {
 
     pub fn move_token(&mut self, count: u8) -> Result<()> {
         if !self.is_valid_move(count) {
-            bail!("Not valid move for token {:#?} and move {:?}", self, count);
+            bail!(Failure::new(
+                ErrorCode::InvalidMove,
+                format!("Not valid move for token {:#?} and move {:?}", self, count)
+            ));
         }
         match self.status {
             Status::Home => self.status = Status::Running { pos: 1 },
@@ -98,7 +135,10 @@ impl Token {
                     self.status = Status::FinalWalk { pos: total }
                 }
             }
-            Status::Done => bail!("Done status does not have any transition"),
+            Status::Done => bail!(Failure::new(
+                ErrorCode::InvalidMove,
+                "Done status does not have any transition"
+            )),
         }
         Ok(())
     }
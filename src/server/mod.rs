@@ -1,2 +1,3 @@
 pub(crate) mod entity;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod service;
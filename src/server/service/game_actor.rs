@@ -0,0 +1,241 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Result};
+use tokio::sync::{
+    broadcast::{self, Receiver, Sender},
+    mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
+    oneshot,
+};
+
+use crate::server::entity::{
+    action::{GameEvent, Response},
+    game::Game,
+};
+
+use super::metrics::Metrics;
+
+// events a slow player can fall behind on, before missing some.
+const EVENT_CAPACITY: usize = 64;
+
+// change made to game by its task, giving back events it caused.
+type Job = Box<dyn FnOnce(&mut Game) -> Vec<GameEvent> + Send>;
+
+enum Order {
+    Apply(Job),
+    // hands game back and ends its task.
+    Stop(oneshot::Sender<Game>),
+}
+
+/// Way in to a game, which runs as its own task and owns its `Game`.
+/// Changes reach game one at a time over a channel, so that games never wait on each other.
+#[derive(Debug, Clone)]
+pub struct GameHandle {
+    id: u32,
+    orders: UnboundedSender<Order>,
+    // every connection playing this game listens here.
+    events: Sender<Response>,
+}
+
+impl GameHandle {
+    /// Starts task running given game. It runs until last handle to it is dropped.
+    pub fn spawn(game: Game, metrics: Metrics) -> GameHandle {
+        let id = game.id();
+        let (orders, received) = unbounded_channel();
+        let (events, _) = broadcast::channel(EVENT_CAPACITY);
+        tokio::spawn(run(game, received, events.clone(), metrics));
+        GameHandle { id, orders, events }
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Reads game without changing it, other than moving its clock to now.
+    pub async fn read<T: Send + 'static>(
+        &self,
+        read: impl FnOnce(&Game) -> T + Send + 'static,
+    ) -> Result<T> {
+        let (sender, receiver) = oneshot::channel();
+        self.send(Box::new(move |game| {
+            let _ = sender.send(read(game));
+            Vec::new()
+        }))?;
+        receiver.await.map_err(|_| self.stopped())
+    }
+
+    /// Changes game and sends events describing change to all its players.
+    pub async fn update<T: Send + 'static>(
+        &self,
+        update: impl FnOnce(&mut Game) -> Result<(T, Vec<GameEvent>)> + Send + 'static,
+    ) -> Result<T> {
+        let (sender, receiver) = oneshot::channel();
+        self.send(Box::new(move |game| match update(game) {
+            Ok((result, events)) => {
+                let _ = sender.send(Ok(result));
+                events
+            }
+            Err(err) => {
+                let _ = sender.send(Err(err));
+                Vec::new()
+            }
+        }))?;
+        receiver.await.map_err(|_| self.stopped())?
+    }
+
+    /// Changes game without waiting for it, for when nobody is left to hear back.
+    pub fn update_later(
+        &self,
+        update: impl FnOnce(&mut Game) -> Vec<GameEvent> + Send + 'static,
+    ) -> Result<()> {
+        self.send(Box::new(update))
+    }
+
+    /// Task of game has ended, like when it panicked, so that nothing sent to it is heard.
+    pub fn is_stopped(&self) -> bool {
+        self.orders.is_closed()
+    }
+
+    pub fn subscribe(&self) -> Receiver<Response> {
+        self.events.subscribe()
+    }
+
+    /// Ends task of game once changes sent before are applied, and gives game back.
+    pub async fn stop(&self) -> Result<Game> {
+        let (sender, receiver) = oneshot::channel();
+        self.orders
+            .send(Order::Stop(sender))
+            .map_err(|_| self.stopped())?;
+        receiver.await.map_err(|_| self.stopped())
+    }
+
+    fn send(&self, job: Job) -> Result<()> {
+        self.orders
+            .send(Order::Apply(job))
+            .map_err(|_| self.stopped())
+    }
+
+    // task of game ended, because it was stopped or it panicked.
+    fn stopped(&self) -> anyhow::Error {
+        anyhow!("Game {} has stopped", self.id)
+    }
+}
+
+// applies changes in order they come, and plays current turn once its time runs out.
+async fn run(
+    mut game: Game,
+    mut orders: UnboundedReceiver<Order>,
+    events: Sender<Response>,
+    metrics: Metrics,
+) {
+    let id = game.id();
+    let mut counted = Counted::new(&metrics, game.status_name());
+    loop {
+        let deadline = game.turn_deadline();
+        let job: Job = tokio::select! {
+            order = orders.recv() => match order {
+                Some(Order::Apply(job)) => job,
+                Some(Order::Stop(sender)) => {
+                    let _ = sender.send(game);
+                    return;
+                }
+                None => return,
+            },
+            _ = sleep_until(deadline) => Box::new(move |game: &mut Game| {
+                let dice = game.next_dice();
+                match game.time_out(dice) {
+                    Ok(events) => events,
+                    Err(err) => {
+                        eprintln!("Failed to time out turn of game {}: {:?}", id, err);
+                        // deadline moves on, rather than passing again right away.
+                        game.restart_turn();
+                        Vec::new()
+                    }
+                }
+            }),
+        };
+        for event in job(game.at(now())) {
+            if let GameEvent::DiceRolled { dice, .. } = event {
+                metrics.dice_rolled(dice);
+            }
+            // nobody listening is not an error.
+            let _ = events.send(Response::Event { game_id: id, event });
+        }
+        counted.moved_to(game.status_name());
+    }
+}
+
+// keeps game counted under its status while its task runs, panics included.
+struct Counted<'a> {
+    metrics: &'a Metrics,
+    status: &'static str,
+}
+
+impl<'a> Counted<'a> {
+    fn new(metrics: &'a Metrics, status: &'static str) -> Self {
+        metrics.game_status(None, Some(status));
+        Counted { metrics, status }
+    }
+
+    fn moved_to(&mut self, status: &'static str) {
+        self.metrics.game_status(Some(self.status), Some(status));
+        self.status = status;
+    }
+}
+
+impl Drop for Counted<'_> {
+    fn drop(&mut self) {
+        self.metrics.game_status(Some(self.status), None);
+    }
+}
+
+// never wakes up without a deadline.
+async fn sleep_until(deadline: Option<u64>) {
+    match deadline {
+        Some(deadline) => {
+            tokio::time::sleep(Duration::from_millis(deadline.saturating_sub(now()))).await
+        }
+        None => std::future::pending().await,
+    }
+}
+
+// milliseconds since unix epoch, which clocks of all games run on.
+pub(super) fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::server::entity::color::Color;
+
+    #[tokio::test]
+    async fn test_panic_stops_only_its_game() {
+        let metrics = Metrics::new().unwrap();
+        let broken = GameHandle::spawn(Game::new(1), metrics.clone());
+        let healthy = GameHandle::spawn(Game::new(2), metrics.clone());
+
+        let panicked = broken
+            .update(|_| -> Result<((), Vec<GameEvent>)> { panic!("bug in a rule") })
+            .await;
+        assert!(panicked.is_err());
+        assert!(broken.read(|game| game.id()).await.is_err());
+
+        let mut events = healthy.subscribe();
+        let color = healthy.update(|game| game.join(Color::Red)).await.unwrap();
+        assert_eq!(color, Color::Red);
+        assert!(matches!(
+            events.recv().await,
+            Ok(Response::Event {
+                game_id: 2,
+                event: GameEvent::PlayerJoined { color: Color::Red }
+            })
+        ));
+        // broken game is no longer counted, once healthy one has caught up.
+        healthy.read(|_| ()).await.unwrap();
+        let text = metrics.render().unwrap();
+        assert!(text.contains(r#"ludo_games{status="Created"} 0"#));
+        assert!(text.contains(r#"ludo_games{status="ColorSelection"} 1"#));
+    }
+}
@@ -0,0 +1,135 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use prometheus::{
+    exponential_buckets, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, Opts,
+    Registry, TextEncoder,
+};
+
+use crate::server::entity::error::ErrorCode;
+
+/// What server is doing, in Prometheus text format at `/metrics`.
+#[derive(Debug, Clone)]
+pub struct Metrics {
+    registry: Registry,
+    connections: IntGauge,
+    games: IntGaugeVec,
+    commands: IntCounterVec,
+    command_seconds: HistogramVec,
+    failures: IntCounterVec,
+    dice: IntCounterVec,
+}
+
+impl Metrics {
+    pub fn new() -> Result<Self> {
+        let connections = IntGauge::new("ludo_connections", "Open websocket connections")?;
+        let games = IntGaugeVec::new(
+            Opts::new("ludo_games", "Running games by their status"),
+            &["status"],
+        )?;
+        let commands = IntCounterVec::new(
+            Opts::new(
+                "ludo_commands_total",
+                "Commands handled, failed ones included",
+            ),
+            &["command"],
+        )?;
+        // 100 microseconds to about 3 seconds.
+        let command_seconds = HistogramVec::new(
+            HistogramOpts::new("ludo_command_duration_seconds", "Time to handle a command")
+                .buckets(exponential_buckets(0.0001, 2., 15)?),
+            &["command"],
+        )?;
+        let failures = IntCounterVec::new(
+            Opts::new(
+                "ludo_command_failures_total",
+                "Commands that failed, by error code",
+            ),
+            &["code"],
+        )?;
+        let dice = IntCounterVec::new(
+            Opts::new(
+                "ludo_dice_rolls_total",
+                "Rolls of every game, by face rolled",
+            ),
+            &["face"],
+        )?;
+
+        let registry = Registry::new();
+        registry.register(Box::new(connections.clone()))?;
+        registry.register(Box::new(games.clone()))?;
+        registry.register(Box::new(commands.clone()))?;
+        registry.register(Box::new(command_seconds.clone()))?;
+        registry.register(Box::new(failures.clone()))?;
+        registry.register(Box::new(dice.clone()))?;
+        Ok(Metrics {
+            registry,
+            connections,
+            games,
+            commands,
+            command_seconds,
+            failures,
+            dice,
+        })
+    }
+
+    pub fn connection_opened(&self) {
+        self.connections.inc();
+    }
+
+    pub fn connection_closed(&self) {
+        self.connections.dec();
+    }
+
+    /// Game moved from one status to another, `None` being before it started running or after it stopped.
+    pub fn game_status(&self, from: Option<&str>, to: Option<&str>) {
+        if from == to {
+            return;
+        }
+        if let Some(from) = from {
+            self.games.with_label_values(&[from]).dec();
+        }
+        if let Some(to) = to {
+            self.games.with_label_values(&[to]).inc();
+        }
+    }
+
+    pub fn command_handled(&self, command: &str, took: Duration, failure: Option<ErrorCode>) {
+        self.commands.with_label_values(&[command]).inc();
+        self.command_seconds
+            .with_label_values(&[command])
+            .observe(took.as_secs_f64());
+        if let Some(code) = failure {
+            self.failures
+                .with_label_values(&[&format!("{:?}", code)])
+                .inc();
+        }
+    }
+
+    pub fn dice_rolled(&self, face: u8) {
+        self.dice.with_label_values(&[&face.to_string()]).inc();
+    }
+
+    pub fn render(&self) -> Result<String> {
+        Ok(TextEncoder::new().encode_to_string(&self.registry.gather())?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_games_move_between_statuses() {
+        let metrics = Metrics::new().unwrap();
+        metrics.game_status(None, Some("Created"));
+        metrics.game_status(None, Some("Created"));
+        metrics.game_status(Some("Created"), Some("InProgress"));
+        metrics.dice_rolled(6);
+
+        let text = metrics.render().unwrap();
+        assert!(text.contains(r#"ludo_games{status="Created"} 1"#));
+        assert!(text.contains(r#"ludo_games{status="InProgress"} 1"#));
+        assert!(text.contains(r#"ludo_dice_rolls_total{face="6"} 1"#));
+    }
+}
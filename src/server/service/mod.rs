@@ -1,2 +1,5 @@
+pub mod game_actor;
+pub mod metrics;
 pub mod server;
 pub mod state;
+pub mod store;
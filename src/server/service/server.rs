@@ -1,80 +1,366 @@
+use std::{sync::Arc, time::Duration};
+
 use axum::{
     extract::{
-        ws::{Message, WebSocket},
-        State, WebSocketUpgrade,
+        ws::{close_code, CloseFrame, Message, WebSocket},
+        Path, State, WebSocketUpgrade,
     },
-    response::Response,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
     routing::get,
-    Router,
+    Json, Router,
 };
 use futures::{
     stream::{SplitSink, SplitStream},
     SinkExt, StreamExt,
 };
-use tokio::sync::mpsc::{channel, Receiver, Sender};
+use serde_json::json;
+use tokio::{
+    sync::{
+        broadcast::{self, error::RecvError},
+        mpsc::{channel, Receiver, Sender},
+        Notify,
+    },
+    task::JoinHandle,
+    time::{self, Instant},
+};
 use tower_http::trace::{DefaultMakeSpan, TraceLayer};
 
-use crate::{server::entity::action::Command, server::entity::action::Response as CommandResponse};
+use crate::{
+    server::entity::action::Response as CommandResponse,
+    server::entity::action::{Command, Request, PROTOCOL_VERSION},
+    server::entity::color::Color,
+    server::entity::encoding::Encoding,
+    server::entity::error::{ErrorCode, Failure},
+};
+
+use super::{
+    game_actor::GameHandle,
+    state::{AppState, Phase},
+    store::GameStore,
+};
+use anyhow::{bail, Result};
+
+// name server introduces itself with.
+const SERVER_NAME: &str = concat!("ludo ", env!("CARGO_PKG_VERSION"));
+// time a closing connection gets to send what is left, close frame included.
+const CLOSE_GRACE: Duration = Duration::from_secs(1);
+// milliseconds clients are told to wait, before a restarted server is up.
+const RECONNECT_AFTER: u64 = 5_000;
+// where games wait while server is down, unless `LUDO_GAMES` points elsewhere.
+const GAMES_FILE: &str = "ludo-games.json";
 
-use super::state::AppState;
-use anyhow::Result;
+/// Game this connection has joined, and as which color.
+/// Game hears that player is gone once session is dropped, like when its connection closes.
+struct Session {
+    game_id: u32,
+    color: Color,
+    game: GameHandle,
+    // pushes events of game to this connection.
+    events: JoinHandle<()>,
+}
+
+impl Session {
+    // events are forwarded from given receiver, so that none sent since it subscribed are missed.
+    fn new(
+        game_id: u32,
+        color: Color,
+        game: GameHandle,
+        events: broadcast::Receiver<CommandResponse>,
+        encoding: Encoding,
+        socket_write_requester: Sender<Message>,
+    ) -> Self {
+        Session {
+            game_id,
+            color,
+            game,
+            events: tokio::spawn(forward_events(events, encoding, socket_write_requester)),
+        }
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        self.events.abort();
+        let color = self.color.clone();
+        // game has already stopped if server is shutting down, restored games count every player as gone anyway.
+        let _ = self.game.update_later(move |game| game.disconnect(&color));
+    }
+}
 
 #[tokio::main]
 async fn main() {
-    axum::Server::bind(&"127.0.0.1:3000".parse().unwrap()).serve(app().into_make_service());
+    let store =
+        GameStore::new(std::env::var("LUDO_GAMES").unwrap_or_else(|_| GAMES_FILE.to_owned()));
+    let state = AppState::new();
+    match state.restore(&store) {
+        Ok(count) => eprintln!("Restored {} games from {:?}", count, store.path()),
+        Err(err) => eprintln!("Failed to restore games: {:?}", err),
+    }
+
+    let server = axum::Server::bind(&"127.0.0.1:3000".parse().unwrap())
+        .serve(router(state.clone()).into_make_service())
+        .with_graceful_shutdown(async move {
+            shutdown_signal().await;
+            if let Err(err) = state.shut_down(RECONNECT_AFTER, &store).await {
+                eprintln!("Failed to save games: {:?}", err);
+            }
+        });
+    if let Err(err) = server.await {
+        eprintln!("Server failed: {:?}", err);
+    }
+}
+
+// ctrl c, or terminate sent by whatever runs server.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
+            Err(err) => {
+                eprintln!("Failed to listen for terminate signal: {:?}", err);
+                std::future::pending::<()>().await
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => (),
+        _ = terminate => (),
+    }
 }
 
-fn app() -> Router {
-    let app = Router::new()
+fn router(state: AppState) -> Router {
+    Router::new()
         .route("/game", get(handler))
+        .route("/metrics", get(metrics))
+        .route("/health", get(health))
+        .route("/ready", get(ready))
+        .route("/games", get(games))
+        .route("/games/:id", get(game))
         .layer(
             TraceLayer::new_for_http()
                 .make_span_with(DefaultMakeSpan::default().include_headers(true)),
         )
-        .with_state(AppState::new());
-
-    app
+        .with_state(state)
 }
 
 async fn handler(ws: WebSocketUpgrade, State(state): State<AppState>) -> Response {
+    if state.is_shutting_down() {
+        return (StatusCode::SERVICE_UNAVAILABLE, "Server is shutting down").into_response();
+    }
     ws.on_upgrade(|socket| handle_socket(socket, state))
 }
 
-async fn handle_socket(mut socket: WebSocket, state: AppState) {
+async fn metrics(State(state): State<AppState>) -> Response {
+    match state.metrics().render() {
+        Ok(text) => ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], text).into_response(),
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, format!("{:?}", err)).into_response(),
+    }
+}
+
+// server is up, whether or not it takes new connections.
+async fn health() -> Json<serde_json::Value> {
+    Json(json!({ "status": "ok" }))
+}
+
+// server takes new connections, which it stops doing once it is shutting down.
+async fn ready(State(state): State<AppState>) -> Response {
+    if state.is_shutting_down() {
+        let body = Json(json!({ "status": "shutting_down" }));
+        return (StatusCode::SERVICE_UNAVAILABLE, body).into_response();
+    }
+    Json(json!({ "status": "ready" })).into_response()
+}
+
+async fn games(State(state): State<AppState>) -> Response {
+    match state.open_games().await {
+        Ok(games) => Json(games).into_response(),
+        Err(err) => failed(err),
+    }
+}
+
+async fn game(Path(id): Path<u32>, State(state): State<AppState>) -> Response {
+    match state.with_game(id, |game| game.snapshot()).await {
+        Ok(snapshot) => Json(snapshot).into_response(),
+        Err(err) => failed(err),
+    }
+}
+
+// coded json error, like those sent over websocket.
+fn failed(err: anyhow::Error) -> Response {
+    let code = Failure::code_of(&err);
+    let status = match code {
+        ErrorCode::UnknownGame => StatusCode::NOT_FOUND,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    let body = Json(json!({ "code": code, "message": err.to_string() }));
+    (status, body).into_response()
+}
+
+// pings client, and closes connection once client has been quiet for too long.
+// reader and writer end together, whichever of them stops first.
+async fn handle_socket(socket: WebSocket, state: AppState) {
+    let heartbeat = state.heartbeat();
+    // server waits for connections to let go of this while shutting down.
+    let _phase = state.watch_phase();
+    let metrics = state.metrics().clone();
+    metrics.connection_opened();
     let (sender, receiver) = socket.split();
     let (tx, rx) = channel::<Message>(1);
+    let heard = Arc::new(Notify::new());
+
+    let mut reader = tokio::spawn(handle_read(receiver, state, tx.clone(), heard.clone()));
+    let mut writer = tokio::spawn(handle_write(sender, rx));
 
-    tokio::spawn(handle_read(receiver, state.clone(), tx));
+    let mut ping = time::interval_at(Instant::now() + heartbeat.interval, heartbeat.interval);
+    let idle = time::sleep(heartbeat.idle_after);
+    tokio::pin!(idle);
+    loop {
+        tokio::select! {
+            _ = &mut reader => break,
+            _ = &mut writer => break,
+            _ = heard.notified() => idle.as_mut().reset(Instant::now() + heartbeat.idle_after),
+            _ = ping.tick() => {
+                // writer being behind already tells that client is slow, ping can be skipped.
+                let _ = tx.try_send(Message::Ping(Vec::new()));
+            }
+            _ = &mut idle => {
+                eprintln!("Client was quiet for {:?}, closing connection", heartbeat.idle_after);
+                let _ = tx.try_send(Message::Close(Some(CloseFrame {
+                    code: close_code::POLICY,
+                    reason: "Idle for too long".into(),
+                })));
+                break;
+            }
+        }
+    }
 
-    tokio::spawn(handle_write(sender, rx));
+    // dropping reader drops its session, which tells game player is gone.
+    reader.abort();
+    drop(tx);
+    if time::timeout(CLOSE_GRACE, &mut writer).await.is_err() {
+        writer.abort();
+    }
+    metrics.connection_closed();
 }
 
 async fn handle_read(
     mut receiver: SplitStream<WebSocket>,
     state: AppState,
     socket_write_requester: Sender<Message>,
+    heard: Arc<Notify>,
 ) {
-    while let Some(msg) = receiver.next().await {
-        let msg = if let Ok(msg) = msg {
-            msg
-        } else {
-            eprintln!("Client disconnected while server tried receiving");
-            return;
+    // encoding client asked for in its hello, none until then.
+    let mut encoding = None;
+    let mut session = None;
+    let mut phase = state.watch_phase();
+    loop {
+        let msg = tokio::select! {
+            msg = receiver.next() => msg,
+            _ = phase.changed() => {
+                let current = *phase.borrow_and_update();
+                match current {
+                    Phase::Running => continue,
+                    Phase::ShuttingDown { reconnect_after } => {
+                        let shutting_down = CommandResponse::ServerShuttingDown { reconnect_after };
+                        let msg = to_message(encoding.unwrap_or_default(), &shutting_down.reply_to(None));
+                        let _ = socket_write_requester.send(msg).await;
+                        continue;
+                    }
+                    Phase::Closing => {
+                        let close = Message::Close(Some(CloseFrame {
+                            code: close_code::AWAY,
+                            reason: "Server is shutting down".into(),
+                        }));
+                        let _ = socket_write_requester.send(close).await;
+                        return;
+                    }
+                }
+            }
         };
+        let msg = match msg {
+            Some(Ok(msg)) => msg,
+            Some(Err(_)) => {
+                eprintln!("Client disconnected while server tried receiving");
+                return;
+            }
+            None => return,
+        };
+        heard.notify_one();
 
-        let msg = match handle_command(msg, state.clone()) {
-            Ok(msg) => msg,
-            Err(err) => {
-                eprintln!("Failure is {:?}", err);
-                let failure_message = CommandResponse::FailureMessage {
-                    message: "Failed to handler request".to_owned(),
-                }
-                .to_json();
-                Message::Text(failure_message)
+        let (frame_encoding, bytes) = match msg {
+            Message::Text(text) => (Encoding::Json, text.into_bytes()),
+            Message::Binary(bytes) => (Encoding::MessagePack, bytes),
+            // pings are answered by socket itself, pongs only keep connection alive.
+            _ => continue,
+        };
+        // reply goes in encoding of its request, so that reply to hello is still json.
+        let reply_encoding = encoding.unwrap_or(frame_encoding);
+        let (request_id, command) = parse_request(frame_encoding, &bytes);
+        let started = Instant::now();
+        let (name, response) = match command {
+            Ok(command) => {
+                let name = command.name();
+                let response = handle_command(
+                    command,
+                    &state,
+                    &mut encoding,
+                    &mut session,
+                    &socket_write_requester,
+                )
+                .await;
+                (name, response)
             }
+            Err(err) => ("Unknown", Err(err)),
         };
+        let failure = response.as_ref().err().map(Failure::code_of);
+        state
+            .metrics()
+            .command_handled(name, started.elapsed(), failure);
+        let response = response.unwrap_or_else(|err| {
+            eprintln!("Failure is {:?}", err);
+            CommandResponse::Error {
+                code: Failure::code_of(&err),
+                message: format!("{:#}", err),
+            }
+        });
+        let reply = to_message(reply_encoding, &response.reply_to(request_id));
+
+        let _ = socket_write_requester.send(reply).await;
+    }
+}
 
-        let _ = socket_write_requester.send(msg).await;
+// id of request comes back even when rest of it can not be read, if it can be found at all.
+fn parse_request(encoding: Encoding, bytes: &[u8]) -> (Option<u32>, Result<Command>) {
+    match encoding.decode::<Request>(bytes) {
+        Ok(request) => (request.request_id, Ok(request.command)),
+        Err(err) => {
+            let request_id = encoding
+                .decode::<serde_json::Value>(bytes)
+                .ok()
+                .and_then(|value| value.get("request_id")?.as_u64())
+                .map(|id| id as u32);
+            let failure = Failure::new(ErrorCode::BadJson, format!("{:#}", err));
+            (request_id, Err(failure.into()))
+        }
+    }
+}
+
+// frame carrying message in given encoding.
+fn to_message(encoding: Encoding, value: &impl serde::Serialize) -> Message {
+    let bytes = encoding
+        .encode(value)
+        .expect("Replies and events always encode");
+    match encoding {
+        Encoding::Json => {
+            Message::Text(String::from_utf8(bytes).expect("Json is always valid utf8"))
+        }
+        Encoding::MessagePack => Message::Binary(bytes),
     }
 }
 
@@ -85,67 +371,744 @@ async fn handle_write(
     while let Some(msg) = socket_write_receiver.recv().await {
         if sender.send(msg).await.is_err() {
             eprintln!("Client disconnected while server tried sending");
+            return;
+        }
+    }
+}
+
+async fn forward_events(
+    mut events: broadcast::Receiver<CommandResponse>,
+    encoding: Encoding,
+    socket_write_requester: Sender<Message>,
+) {
+    loop {
+        let event = match events.recv().await {
+            Ok(event) => event,
+            Err(RecvError::Lagged(missed)) => {
+                eprintln!("Client is too slow and missed {} events", missed);
+                continue;
+            }
+            Err(RecvError::Closed) => return,
+        };
+        if socket_write_requester
+            .send(to_message(encoding, &event.reply_to(None)))
+            .await
+            .is_err()
+        {
+            return;
         }
     }
 }
 
-fn handle_command(msg: Message, state: AppState) -> Result<Message> {
-    let msg = msg.into_text()?;
-    let command = serde_json::from_str::<Command>(&msg)?;
+async fn handle_command(
+    command: Command,
+    state: &AppState,
+    encoding: &mut Option<Encoding>,
+    session: &mut Option<Session>,
+    socket_write_requester: &Sender<Message>,
+) -> Result<CommandResponse> {
+    if encoding.is_none() && !matches!(command, Command::Hello { .. }) {
+        bail!(Failure::new(
+            ErrorCode::HelloFirst,
+            "Send hello before any other command"
+        ));
+    }
     let response = match command {
-        Command::CreateGame => {
-            let game_id = state.create_game()?;
+        Command::Hello {
+            protocol_version,
+            client_name,
+            encoding: asked,
+        } => {
+            if protocol_version != PROTOCOL_VERSION {
+                bail!(Failure::new(
+                    ErrorCode::UnsupportedVersion,
+                    format!(
+                        "Server speaks protocol {} but {} speaks {}",
+                        PROTOCOL_VERSION, client_name, protocol_version
+                    )
+                ));
+            }
+            *encoding = Some(asked);
+            CommandResponse::Hello {
+                protocol_version: PROTOCOL_VERSION,
+                server_name: SERVER_NAME.to_owned(),
+                encoding: asked,
+            }
+        }
+        Command::CreateGame {
+            time_control,
+            untimed,
+        } => {
+            let game_id = state.create_game(time_control, untimed)?;
             CommandResponse::CreateGameResponse { game_id }
         }
-        Command::AvailableColors { .. } => todo!(),
-        Command::JoinGame { .. } => todo!(),
-        Command::StartGame { .. } => todo!(),
+        Command::AvailableColors { id } => CommandResponse::make_available_colors(
+            state.with_game(id, |game| game.available_colors()).await?,
+        ),
+        Command::JoinGame {
+            id,
+            color,
+            client_seed,
+        } => {
+            let game = state.game(id)?;
+            // listen before joining, so that player also hears about its own joining.
+            let events = game.subscribe();
+            let (color, rejoin_token) = game
+                .update(|game| {
+                    let (color, events) = game.join_with_seed(color, client_seed)?;
+                    let rejoin_token = game.rejoin_token(&color).unwrap_or_default().to_owned();
+                    Ok(((color, rejoin_token), events))
+                })
+                .await?;
+            *session = Some(Session::new(
+                id,
+                color.clone(),
+                game,
+                events,
+                encoding.unwrap_or_default(),
+                socket_write_requester.clone(),
+            ));
+            CommandResponse::PickedColor {
+                color,
+                rejoin_token,
+            }
+        }
+        Command::Rejoin {
+            id,
+            color,
+            rejoin_token,
+        } => {
+            let game = state.game(id)?;
+            let events = game.subscribe();
+            let snapshot = {
+                let color = color.clone();
+                game.update(move |game| {
+                    let events = game.rejoin(&color, &rejoin_token)?;
+                    Ok((game.snapshot(), events))
+                })
+                .await?
+            };
+            *session = Some(Session::new(
+                id,
+                color,
+                game,
+                events,
+                encoding.unwrap_or_default(),
+                socket_write_requester.clone(),
+            ));
+            CommandResponse::Snapshot { snapshot }
+        }
+        Command::StartGame { id } => {
+            joined(session, id)?;
+            state
+                .update_game(id, |game| Ok(((), game.start()?)))
+                .await?;
+            CommandResponse::Accepted
+        }
+        Command::RollDice { id } => {
+            let color = joined(session, id)?.clone();
+            state
+                .update_game(id, move |game| {
+                    let dice = game.next_dice();
+                    Ok(((), game.roll(&color, dice)?))
+                })
+                .await?;
+            CommandResponse::Accepted
+        }
+        Command::MoveToken { id, token_id } => {
+            let color = joined(session, id)?.clone();
+            state
+                .update_game(id, move |game| Ok(((), game.move_token(&color, token_id)?)))
+                .await?;
+            CommandResponse::Accepted
+        }
+        Command::Snapshot { id } => CommandResponse::Snapshot {
+            snapshot: state.with_game(id, |game| game.snapshot()).await?,
+        },
     };
 
-    let stringified = serde_json::to_string(&response)?;
-    Ok(Message::Text(stringified))
+    Ok(response)
+}
+
+// color this connection plays with in given game.
+fn joined(session: &Option<Session>, game_id: u32) -> Result<&Color> {
+    match session {
+        Some(session) if session.game_id == game_id => Ok(&session.color),
+        _ => bail!(Failure::new(
+            ErrorCode::NotJoined,
+            format!("Join game {} first", game_id)
+        )),
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use std::net::{Ipv4Addr, SocketAddr};
+    use std::{
+        collections::VecDeque,
+        net::{Ipv4Addr, SocketAddr},
+    };
 
     use futures::{SinkExt, StreamExt};
+    use rstest::rstest;
+    use serde::Serialize;
+    use tokio::net::TcpStream;
+    use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
 
     use crate::{
-        server::entity::action::Command, server::entity::action::Response as CommandResponse,
+        server::entity::action::Command,
+        server::entity::action::Response as CommandResponse,
+        server::entity::action::{GameEvent, GameSnapshot, GameSummary, Reply},
     };
 
     use super::*;
+    use crate::server::service::state::Heartbeat;
 
-    #[tokio::test]
-    async fn integration_test() {
+    type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+    async fn send(socket: &mut Socket, encoding: Encoding, value: &impl Serialize) {
+        let bytes = encoding.encode(value).unwrap();
+        let msg = match encoding {
+            Encoding::Json => tungstenite::Message::text(String::from_utf8(bytes).unwrap()),
+            Encoding::MessagePack => tungstenite::Message::binary(bytes),
+        };
+        socket.send(msg).await.unwrap();
+    }
+
+    // pings are answered by socket while it is read, and are skipped here.
+    async fn receive(socket: &mut Socket, encoding: Encoding) -> Reply {
+        loop {
+            match (encoding, socket.next().await.unwrap().unwrap()) {
+                (_, tungstenite::Message::Ping(_)) => continue,
+                (Encoding::Json, tungstenite::Message::Text(msg)) => {
+                    return encoding.decode(msg.as_bytes()).unwrap()
+                }
+                (Encoding::MessagePack, tungstenite::Message::Binary(msg)) => {
+                    return encoding.decode(&msg).unwrap()
+                }
+                (_, other) => panic!("expected {:?} message but got {:#?}", encoding, other),
+            }
+        }
+    }
+
+    async fn exchange(socket: &mut Socket, encoding: Encoding, request: &impl Serialize) -> Reply {
+        send(socket, encoding, request).await;
+        receive(socket, encoding).await
+    }
+
+    // serves state on a free port, for as long as test runs.
+    fn serve(state: AppState) -> SocketAddr {
         let socket_addr = SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0));
-        let app = axum::Server::bind(&socket_addr).serve(app().into_make_service());
+        let app = axum::Server::bind(&socket_addr).serve(router(state).into_make_service());
         let local_address = app.local_addr();
         tokio::spawn(app);
+        local_address
+    }
+
+    fn hello(encoding: Encoding) -> Command {
+        Command::Hello {
+            protocol_version: PROTOCOL_VERSION,
+            client_name: "test".to_owned(),
+            encoding,
+        }
+    }
+
+    // replies and events can arrive in any order, events are kept aside while waiting for reply.
+    struct Client {
+        socket: Socket,
+        encoding: Encoding,
+        events: VecDeque<GameEvent>,
+        next_request_id: u32,
+    }
+
+    impl Client {
+        async fn connect(address: SocketAddr, encoding: Encoding) -> Client {
+            let (mut socket, _) = tokio_tungstenite::connect_async(format!("ws://{address}/game"))
+                .await
+                .unwrap();
+            let hello = exchange(&mut socket, Encoding::Json, &hello(encoding)).await;
+            assert!(matches!(
+                hello.response,
+                CommandResponse::Hello { encoding: picked, .. } if picked == encoding
+            ));
+            Client {
+                socket,
+                encoding,
+                events: VecDeque::new(),
+                next_request_id: 1,
+            }
+        }
+
+        async fn receive(&mut self) -> Reply {
+            receive(&mut self.socket, self.encoding).await
+        }
+
+        async fn request(&mut self, command: Command) -> CommandResponse {
+            let request_id = self.next_request_id;
+            self.next_request_id += 1;
+            let request = Request {
+                request_id: Some(request_id),
+                command,
+            };
+            send(&mut self.socket, self.encoding, &request).await;
+            loop {
+                match self.receive().await {
+                    Reply {
+                        response: CommandResponse::Event { event, .. },
+                        ..
+                    } => self.events.push_back(event),
+                    reply => {
+                        assert_eq!(reply.request_id, Some(request_id));
+                        return reply.response;
+                    }
+                }
+            }
+        }
+
+        async fn create_game(&mut self) -> u32 {
+            match self
+                .request(Command::CreateGame {
+                    time_control: None,
+                    untimed: false,
+                })
+                .await
+            {
+                CommandResponse::CreateGameResponse { game_id } => game_id,
+                other => panic!("expected game id but got {:#?}", other),
+            }
+        }
+
+        async fn join(&mut self, id: u32, color: Color) -> CommandResponse {
+            self.request(Command::JoinGame {
+                id,
+                color,
+                client_seed: None,
+            })
+            .await
+        }
+
+        async fn event(&mut self) -> GameEvent {
+            if let Some(event) = self.events.pop_front() {
+                return event;
+            }
+            match self.receive().await.response {
+                CommandResponse::Event { event, .. } => event,
+                reply => panic!("expected event but got {:#?}", reply),
+            }
+        }
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_players_hear_each_others_rolls(
+        #[values(Encoding::Json, Encoding::MessagePack)] encoding: Encoding,
+    ) {
+        let local_address = serve(AppState::new());
+
+        let mut yellow = Client::connect(local_address, encoding).await;
+        let mut red = Client::connect(local_address, encoding).await;
+        let id = yellow.create_game().await;
+        assert_eq!(
+            red.request(Command::RollDice { id }).await,
+            CommandResponse::Error {
+                code: ErrorCode::NotJoined,
+                message: format!("Join game {} first", id)
+            }
+        );
+
+        for (client, color) in [(&mut yellow, Color::Yellow), (&mut red, Color::Red)] {
+            let picked = client
+                .request(Command::JoinGame {
+                    id,
+                    color: color.clone(),
+                    client_seed: Some(format!("{:?}", color)),
+                })
+                .await;
+            assert!(matches!(
+                picked,
+                CommandResponse::PickedColor { color: picked, rejoin_token }
+                    if picked == color && rejoin_token.len() == 32
+            ));
+        }
+        assert_eq!(
+            red.request(Command::AvailableColors { id }).await,
+            CommandResponse::make_available_colors(vec![Color::Blue, Color::Green])
+        );
+        assert_eq!(
+            red.request(Command::StartGame { id }).await,
+            CommandResponse::Accepted
+        );
+        assert!(matches!(
+            red.request(Command::RollDice { id }).await,
+            CommandResponse::Error {
+                code: ErrorCode::NotYourTurn,
+                ..
+            }
+        ));
+        assert_eq!(
+            yellow.request(Command::RollDice { id }).await,
+            CommandResponse::Accepted
+        );
+
+        for client in [&mut yellow, &mut red] {
+            let mut events = Vec::new();
+            loop {
+                let event = client.event().await;
+                let rolled = matches!(event, GameEvent::DiceRolled { .. });
+                events.push(event);
+                if rolled {
+                    break;
+                }
+            }
+            assert_eq!(
+                events[events.len() - 3..events.len() - 1],
+                [
+                    GameEvent::GameStarted {
+                        players: vec![Color::Yellow, Color::Red]
+                    },
+                    GameEvent::TurnChanged {
+                        color: Color::Yellow
+                    },
+                ]
+            );
+            assert!(matches!(
+                events.last(),
+                Some(GameEvent::DiceRolled { color: Color::Yellow, dice }) if (1..=6).contains(dice)
+            ));
+        }
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn integration_test(#[values(Encoding::Json, Encoding::MessagePack)] encoding: Encoding) {
+        let local_address = serve(AppState::new());
 
         let (mut socket, _) =
             tokio_tungstenite::connect_async(format!("ws://{local_address}/game"))
                 .await
                 .unwrap();
 
+        let create_game = Command::CreateGame {
+            time_control: None,
+            untimed: false,
+        };
+        let reply = exchange(&mut socket, encoding, &create_game).await;
+        assert!(matches!(
+            reply.response,
+            CommandResponse::Error {
+                code: ErrorCode::HelloFirst,
+                ..
+            }
+        ));
+        let old_hello =
+            serde_json::json!({"type": "Hello", "protocol_version": 0, "client_name": "old"});
+        let reply = exchange(&mut socket, Encoding::Json, &old_hello).await;
+        assert!(matches!(
+            reply.response,
+            CommandResponse::Error {
+                code: ErrorCode::UnsupportedVersion,
+                ..
+            }
+        ));
+        let reply = exchange(&mut socket, Encoding::Json, &hello(encoding)).await;
+        assert!(matches!(reply.response, CommandResponse::Hello { .. }));
+        let dance = serde_json::json!({"type": "Dance", "request_id": 5});
+        let reply = exchange(&mut socket, encoding, &dance).await;
+        assert_eq!(reply.request_id, Some(5));
+        assert!(matches!(
+            reply.response,
+            CommandResponse::Error {
+                code: ErrorCode::BadJson,
+                ..
+            }
+        ));
+
         for i in 1..10 {
-            let request = serde_json::to_string(&Command::CreateGame).unwrap();
-            socket
-                .send(tungstenite::Message::text(request))
+            let request = Request {
+                request_id: Some(100 + i),
+                command: Command::CreateGame {
+                    time_control: None,
+                    untimed: false,
+                },
+            };
+            assert_eq!(
+                exchange(&mut socket, encoding, &request).await,
+                CommandResponse::CreateGameResponse { game_id: i }.reply_to(Some(100 + i))
+            );
+        }
+    }
+
+    // status and body of a plain http get, which is all tests need of http.
+    async fn get(address: SocketAddr, path: &str) -> (u16, String) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        let request = format!("GET {} HTTP/1.0\r\nHost: {}\r\n\r\n", path, address);
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+        (status, body.to_owned())
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_lobby_lists_open_games(
+        #[values(Encoding::Json, Encoding::MessagePack)] encoding: Encoding,
+    ) {
+        let local_address = serve(AppState::new());
+
+        let mut red = Client::connect(local_address, encoding).await;
+        for _ in 0..2 {
+            red.create_game().await;
+        }
+        red.join(2, Color::Red).await;
+
+        assert_eq!(
+            get(local_address, "/health").await,
+            (200, r#"{"status":"ok"}"#.to_owned())
+        );
+        assert_eq!(
+            get(local_address, "/ready").await,
+            (200, r#"{"status":"ready"}"#.to_owned())
+        );
+
+        let (status, body) = get(local_address, "/games").await;
+        assert_eq!(status, 200);
+        let games: Vec<GameSummary> = serde_json::from_str(&body).unwrap();
+        assert_eq!(games.iter().map(|game| game.id).collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(games[0].status, "Created");
+        assert_eq!(games[1].status, "ColorSelection");
+        assert_eq!(games[1].colors, [Color::Red]);
+        assert!(games.iter().all(|game| game.created > 0));
+
+        let (status, body) = get(local_address, "/games/2").await;
+        assert_eq!(status, 200);
+        let snapshot: GameSnapshot = serde_json::from_str(&body).unwrap();
+        assert_eq!(snapshot.id, 2);
+        assert_eq!(snapshot.players[0].color, Color::Red);
+
+        let (status, body) = get(local_address, "/games/9").await;
+        assert_eq!(status, 404);
+        assert!(body.contains(r#""code":"UnknownGame""#), "{}", body);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_metrics_count_commands_and_dice(
+        #[values(Encoding::Json, Encoding::MessagePack)] encoding: Encoding,
+    ) {
+        let local_address = serve(AppState::new());
+
+        let mut yellow = Client::connect(local_address, encoding).await;
+        let mut red = Client::connect(local_address, encoding).await;
+        let id = yellow.create_game().await;
+        red.request(Command::RollDice { id }).await;
+        for (client, color) in [(&mut yellow, Color::Yellow), (&mut red, Color::Red)] {
+            client.join(id, color).await;
+        }
+        red.request(Command::StartGame { id }).await;
+        yellow.request(Command::RollDice { id }).await;
+
+        let (status, metrics) = get(local_address, "/metrics").await;
+        assert_eq!(status, 200);
+        let lines: Vec<&str> = metrics.lines().collect();
+        for expected in [
+            "ludo_connections 2",
+            r#"ludo_games{status="InProgress"} 1"#,
+            r#"ludo_commands_total{command="RollDice"} 2"#,
+            r#"ludo_commands_total{command="JoinGame"} 2"#,
+            r#"ludo_command_failures_total{code="NotJoined"} 1"#,
+            r#"ludo_command_duration_seconds_count{command="StartGame"} 1"#,
+        ] {
+            assert!(
+                lines.contains(&expected),
+                "{} is not in {}",
+                expected,
+                metrics
+            );
+        }
+        let rolls: u64 = lines
+            .iter()
+            .filter(|line| line.starts_with("ludo_dice_rolls_total{"))
+            .map(|line| line.rsplit(' ').next().unwrap().parse::<u64>().unwrap())
+            .sum();
+        assert_eq!(rolls, 1);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_shutdown_saves_games_for_next_server(
+        #[values(Encoding::Json, Encoding::MessagePack)] encoding: Encoding,
+    ) {
+        let file = format!("ludo-shutdown-{}-{:?}.json", std::process::id(), encoding);
+        let store = GameStore::new(std::env::temp_dir().join(file));
+        let state = AppState::new();
+        let local_address = serve(state.clone());
+
+        let mut yellow = Client::connect(local_address, encoding).await;
+        let id = yellow.create_game().await;
+        yellow.join(id, Color::Yellow).await;
+
+        let shut_down = {
+            let (state, store) = (state.clone(), store.clone());
+            tokio::spawn(async move { state.shut_down(3_000, &store).await })
+        };
+        loop {
+            match yellow.receive().await.response {
+                CommandResponse::ServerShuttingDown { reconnect_after } => {
+                    assert_eq!(reconnect_after, 3_000);
+                    break;
+                }
+                CommandResponse::Event { .. } => continue,
+                other => panic!("expected shutdown but got {:#?}", other),
+            }
+        }
+        loop {
+            match yellow.socket.next().await {
+                Some(Ok(tungstenite::Message::Close(Some(frame)))) => {
+                    assert_eq!(frame.reason, "Server is shutting down");
+                    break;
+                }
+                Some(Ok(_)) => continue,
+                other => panic!("expected close frame but got {:#?}", other),
+            }
+        }
+        shut_down.await.unwrap().unwrap();
+        assert!(
+            tokio_tungstenite::connect_async(format!("ws://{local_address}/game"))
                 .await
-                .unwrap();
+                .is_err()
+        );
 
-            let msg = match socket.next().await.unwrap().unwrap() {
-                tungstenite::Message::Text(msg) => msg,
-                other => panic!("expected text message but got {:#?}", other),
+        let restarted = AppState::new();
+        assert_eq!(restarted.restore(&store).unwrap(), 1);
+        assert_eq!(
+            restarted
+                .with_game(id, |game| game.available_colors())
+                .await
+                .unwrap(),
+            Color::ALL.to_vec()
+        );
+        assert_eq!(restarted.restore(&store).unwrap(), 0);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_restored_game_carries_on_with_rejoined_players(
+        #[values(Encoding::Json, Encoding::MessagePack)] encoding: Encoding,
+    ) {
+        let file = format!("ludo-rejoin-{}-{:?}.json", std::process::id(), encoding);
+        let store = GameStore::new(std::env::temp_dir().join(file));
+        let state = AppState::new();
+        let local_address = serve(state.clone());
+
+        let mut yellow = Client::connect(local_address, encoding).await;
+        let mut red = Client::connect(local_address, encoding).await;
+        let id = yellow.create_game().await;
+        let mut rejoin_tokens = Vec::new();
+        for (client, color) in [(&mut yellow, Color::Yellow), (&mut red, Color::Red)] {
+            match client.join(id, color).await {
+                CommandResponse::PickedColor { rejoin_token, .. } => {
+                    rejoin_tokens.push(rejoin_token)
+                }
+                other => panic!("expected color but got {:#?}", other),
+            }
+        }
+        red.request(Command::StartGame { id }).await;
+        state.shut_down(RECONNECT_AFTER, &store).await.unwrap();
+
+        let restarted = AppState::new();
+        assert_eq!(restarted.restore(&store).unwrap(), 1);
+        let local_address = serve(restarted);
+        let mut yellow = Client::connect(local_address, encoding).await;
+        let mut red = Client::connect(local_address, encoding).await;
+        assert!(matches!(
+            red.request(Command::Rejoin {
+                id,
+                color: Color::Red,
+                rejoin_token: rejoin_tokens[0].clone(),
+            })
+            .await,
+            CommandResponse::Error {
+                code: ErrorCode::NotJoined,
+                ..
+            }
+        ));
+        for (client, color, rejoin_token) in [
+            (&mut yellow, Color::Yellow, &rejoin_tokens[0]),
+            (&mut red, Color::Red, &rejoin_tokens[1]),
+        ] {
+            let snapshot = match client
+                .request(Command::Rejoin {
+                    id,
+                    color: color.clone(),
+                    rejoin_token: rejoin_token.clone(),
+                })
+                .await
+            {
+                CommandResponse::Snapshot { snapshot } => snapshot,
+                other => panic!("expected snapshot but got {:#?}", other),
             };
+            assert_eq!(snapshot.turn, Some(Color::Yellow));
+            let player = snapshot
+                .players
+                .iter()
+                .find(|player| player.color == color)
+                .unwrap();
+            assert!(!player.disconnected && !player.away);
+        }
 
-            assert_eq!(
-                serde_json::from_str::<CommandResponse>(&msg).unwrap(),
-                CommandResponse::CreateGameResponse { game_id: i }
-            );
+        assert_eq!(
+            yellow.request(Command::RollDice { id }).await,
+            CommandResponse::Accepted
+        );
+        loop {
+            if let GameEvent::DiceRolled { color, .. } = red.event().await {
+                assert_eq!(color, Color::Yellow);
+                break;
+            }
+        }
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_quiet_player_is_dropped_from_game(
+        #[values(Encoding::Json, Encoding::MessagePack)] encoding: Encoding,
+    ) {
+        let heartbeat = Heartbeat {
+            interval: Duration::from_millis(50),
+            idle_after: Duration::from_millis(300),
+        };
+        let local_address = serve(AppState::new().with_heartbeat(heartbeat));
+
+        let mut yellow = Client::connect(local_address, encoding).await;
+        let mut red = Client::connect(local_address, encoding).await;
+        let id = yellow.create_game().await;
+        for (client, color) in [(&mut yellow, Color::Yellow), (&mut red, Color::Red)] {
+            client.join(id, color).await;
+        }
+
+        // red keeps reading, so it answers pings and outlives idle deadline. yellow does not.
+        loop {
+            if red.event().await
+                == (GameEvent::PlayerLeft {
+                    color: Color::Yellow,
+                })
+            {
+                break;
+            }
+        }
+        assert_eq!(
+            red.request(Command::AvailableColors { id }).await,
+            CommandResponse::make_available_colors(vec![Color::Yellow, Color::Blue, Color::Green])
+        );
+        // yellow may fail to answer pings it has queued, before reading close frame.
+        loop {
+            match yellow.socket.next().await {
+                Some(Ok(tungstenite::Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => continue,
+            }
         }
     }
 }
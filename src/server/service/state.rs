@@ -1,38 +1,294 @@
 use std::{
     collections::HashMap,
-    sync::{Arc, Mutex},
+    fmt::Debug,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, RwLock,
+    },
+    time::Duration,
 };
 
-use crate::server::entity::game::Game;
+use tokio::sync::watch;
+
+use crate::server::entity::{
+    action::{GameEvent, GameSummary, TimeControl},
+    error::{ErrorCode, Failure},
+    game::Game,
+};
 use anyhow::{anyhow, Result};
 
+use super::{
+    game_actor::{now, GameHandle},
+    metrics::Metrics,
+    store::GameStore,
+};
+
+// time connections get to close once server has told them to, before server stops anyway.
+const CLOSE_WAIT: Duration = Duration::from_secs(2);
+// time players of a restored game get to rejoin, before their turns are played for them.
+const REJOIN_GRACE: Duration = Duration::from_secs(30);
+
+/// Registry of running games. Each game is its own task, see `GameHandle`,
+/// so registry is only locked to look games up or add them.
 #[derive(Debug, Clone)]
 pub struct AppState {
-    games: Arc<Mutex<HashMap<u32, Game>>>,
+    games: Arc<RwLock<HashMap<u32, GameHandle>>>,
+    // ids are never given twice, even once their games are gone.
+    last_id: Arc<AtomicU32>,
+    // for games created without asking for one.
+    time_control: TimeControl,
+    heartbeat: Heartbeat,
+    // every connection watches this, to learn when server is going down.
+    phase: Arc<watch::Sender<Phase>>,
+    metrics: Metrics,
+}
+
+/// Where server is in its life, as far as connections care.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Running,
+    /// Connections tell their clients to come back after given milliseconds, and no new ones are accepted.
+    ShuttingDown {
+        reconnect_after: u64,
+    },
+    /// Games are saved, connections close.
+    Closing,
+}
+
+/// How often server pings every connection, and how long a connection may stay quiet before it is closed.
+/// Pongs count as hearing from client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Heartbeat {
+    pub interval: Duration,
+    pub idle_after: Duration,
+}
+
+impl Default for Heartbeat {
+    fn default() -> Self {
+        Heartbeat {
+            interval: Duration::from_secs(15),
+            idle_after: Duration::from_secs(45),
+        }
+    }
 }
 
 impl AppState {
     pub fn new() -> AppState {
+        AppState::with_time_control(TimeControl::default())
+    }
+
+    pub fn with_time_control(time_control: TimeControl) -> AppState {
         AppState {
-            games: Arc::new(Mutex::new(HashMap::new())),
+            games: Arc::new(RwLock::new(HashMap::new())),
+            last_id: Arc::new(AtomicU32::new(0)),
+            time_control,
+            heartbeat: Heartbeat::default(),
+            phase: Arc::new(watch::channel(Phase::Running).0),
+            metrics: Metrics::new().expect("Metrics have names of their own"),
         }
     }
 
-    pub fn create_game(&self) -> Result<u32> {
-        let mut current_game = self
-            .games
-            .lock()
-            .map_err(|err| anyhow!(format!("Failed to lock with error {:#?}", err)))?;
+    pub fn with_heartbeat(self, heartbeat: Heartbeat) -> AppState {
+        AppState { heartbeat, ..self }
+    }
+
+    pub fn heartbeat(&self) -> Heartbeat {
+        self.heartbeat
+    }
+
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+
+    pub fn watch_phase(&self) -> watch::Receiver<Phase> {
+        self.phase.subscribe()
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        *self.phase.borrow() != Phase::Running
+    }
+
+    /// Carries on with games saved by last server to shut down. Returns how many there were.
+    pub fn restore(&self, store: &GameStore) -> Result<usize> {
+        let games = store.take()?;
+        let count = games.len();
+        let mut registry = self.games.write().map_err(lock_failed)?;
+        for game in games {
+            let handle = GameHandle::spawn(game, self.metrics.clone());
+            // time server was down is not taken from player whose turn it was.
+            handle.update_later(|game| {
+                game.resume(REJOIN_GRACE.as_millis() as u64);
+                Vec::new()
+            })?;
+            self.last_id.fetch_max(handle.id(), Ordering::SeqCst);
+            registry.insert(handle.id(), handle);
+        }
+        Ok(count)
+    }
 
-        let next_key = current_game
-            .keys()
-            .max()
-            .or(Some(&0))
-            .ok_or_else(|| anyhow!("Failed to get max key"))?
-            + 1;
+    /// Tells every connection that server is going down, stops and saves all games,
+    /// then closes connections and waits a little for them to go.
+    pub async fn shut_down(&self, reconnect_after: u64, store: &GameStore) -> Result<()> {
+        self.phase
+            .send_replace(Phase::ShuttingDown { reconnect_after });
 
-        current_game.insert(next_key, Game::new(next_key));
+        let handles: Vec<GameHandle> = self
+            .games
+            .write()
+            .map_err(lock_failed)?
+            .drain()
+            .map(|(_, handle)| handle)
+            .collect();
+        let mut games = Vec::new();
+        for handle in handles {
+            match handle.stop().await {
+                Ok(game) => games.push(game),
+                Err(err) => eprintln!("Failed to save game: {:?}", err),
+            }
+        }
+        let saved = store.save(&games);
+
+        self.phase.send_replace(Phase::Closing);
+        let _ = tokio::time::timeout(CLOSE_WAIT, self.phase.closed()).await;
+        saved
+    }
 
+    /// Untimed game has no clock, so it can not be given a time control as well.
+    pub fn create_game(&self, time_control: Option<TimeControl>, untimed: bool) -> Result<u32> {
+        let time_control = match (time_control, untimed) {
+            (time_control, false) => Some(time_control.unwrap_or(self.time_control)),
+            (None, true) => None,
+            (Some(_), true) => {
+                return Err(Failure::new(
+                    ErrorCode::BadJson,
+                    "Untimed game takes no time control".to_owned(),
+                )
+                .into())
+            }
+        };
+        let mut games = self.games.write().map_err(lock_failed)?;
+        let next_key = self.last_id.fetch_add(1, Ordering::SeqCst) + 1;
+        let game = match time_control {
+            Some(time_control) => Game::with_time_control(next_key, time_control),
+            None => Game::new(next_key),
+        }
+        .created_at(now());
+        games.insert(next_key, GameHandle::spawn(game, self.metrics.clone()));
         Ok(next_key)
     }
+
+    /// Game which has stopped running is gone for good, and taken out of registry once it is found.
+    pub fn game(&self, id: u32) -> Result<GameHandle> {
+        let handle = self.games.read().map_err(lock_failed)?.get(&id).cloned();
+        match handle {
+            Some(handle) if handle.is_stopped() => {
+                self.games.write().map_err(lock_failed)?.remove(&id);
+                Err(unknown_game(id).into())
+            }
+            Some(handle) => Ok(handle),
+            None => Err(unknown_game(id).into()),
+        }
+    }
+
+    /// Games yet to end, oldest first. Games which have stopped running are taken out of registry.
+    pub async fn open_games(&self) -> Result<Vec<GameSummary>> {
+        let mut handles: Vec<GameHandle> = {
+            let mut games = self.games.write().map_err(lock_failed)?;
+            games.retain(|_, handle| !handle.is_stopped());
+            games.values().cloned().collect()
+        };
+        handles.sort_by_key(|handle| handle.id());
+        let mut games = Vec::new();
+        for handle in handles {
+            if let Ok(Some(game)) = handle
+                .read(|game| game.is_open().then(|| game.summary()))
+                .await
+            {
+                games.push(game);
+            }
+        }
+        Ok(games)
+    }
+
+    /// Reads game without changing it, other than moving its clock to now.
+    pub async fn with_game<T: Send + 'static>(
+        &self,
+        id: u32,
+        read: impl FnOnce(&Game) -> T + Send + 'static,
+    ) -> Result<T> {
+        self.game(id)?.read(read).await
+    }
+
+    /// Changes game and sends events describing change to all its players.
+    pub async fn update_game<T: Send + 'static>(
+        &self,
+        id: u32,
+        update: impl FnOnce(&mut Game) -> Result<(T, Vec<GameEvent>)> + Send + 'static,
+    ) -> Result<T> {
+        self.game(id)?.update(update).await
+    }
+}
+
+fn lock_failed(err: impl Debug) -> anyhow::Error {
+    anyhow!(format!("Failed to lock with error {:#?}", err))
+}
+
+fn unknown_game(id: u32) -> Failure {
+    Failure::new(ErrorCode::UnknownGame, format!("There is no game {}", id))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::server::entity::color::Color;
+
+    #[tokio::test]
+    async fn test_stopped_game_is_unknown() {
+        let state = AppState::new();
+        let healthy = state.create_game(None, false).unwrap();
+        let broken = state.create_game(None, false).unwrap();
+        let panicked = state
+            .update_game(broken, |_| -> Result<((), Vec<GameEvent>)> {
+                panic!("bug in a rule")
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(Failure::code_of(&panicked), ErrorCode::Internal);
+
+        let gone = state.game(broken).unwrap_err();
+        assert_eq!(Failure::code_of(&gone), ErrorCode::UnknownGame);
+        let ids: Vec<u32> = state
+            .open_games()
+            .await
+            .unwrap()
+            .iter()
+            .map(|game| game.id)
+            .collect();
+        assert_eq!(ids, [healthy]);
+        // id of stopped game is not given to a new one.
+        assert_eq!(state.create_game(None, false).unwrap(), broken + 1);
+    }
+
+    #[tokio::test]
+    async fn test_untimed_game_is_asked_for() {
+        let state = AppState::new();
+        let conflicting = state
+            .create_game(Some(TimeControl::default()), true)
+            .unwrap_err();
+        assert_eq!(Failure::code_of(&conflicting), ErrorCode::BadJson);
+
+        for (untimed, timed) in [(true, false), (false, true)] {
+            let id = state.create_game(None, untimed).unwrap();
+            state
+                .update_game(id, |game| {
+                    game.join(Color::Yellow)?;
+                    game.join(Color::Red)?;
+                    Ok(((), game.start()?))
+                })
+                .await
+                .unwrap();
+            let deadline = state.with_game(id, |game| game.turn_deadline()).await;
+            assert_eq!(deadline.unwrap().is_some(), timed);
+        }
+    }
 }
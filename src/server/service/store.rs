@@ -0,0 +1,50 @@
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+
+use crate::server::entity::game::Game;
+
+/// Games kept in a json file while server is down, so that a restarted server can carry on with them.
+#[derive(Debug, Clone)]
+pub struct GameStore {
+    path: PathBuf,
+}
+
+impl GameStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        GameStore { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Writes a file next to store first, so that a crash half way leaves last saved games as they were.
+    pub fn save(&self, games: &[Game]) -> Result<()> {
+        let json = serde_json::to_string(games)?;
+        let written = self.path.with_extension("tmp");
+        fs::write(&written, json)
+            .map_err(|err| anyhow!("Failed to write {:?} {}", written, err))?;
+        fs::rename(&written, &self.path)
+            .map_err(|err| anyhow!("Failed to replace {:?} {}", self.path, err))
+    }
+
+    /// Games saved last, which are taken out of store so that they are restored only once.
+    /// No file means there is nothing to restore.
+    pub fn take(&self) -> Result<Vec<Game>> {
+        let json = match fs::read_to_string(&self.path) {
+            Ok(json) => json,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(anyhow!("Failed to read {:?} {}", self.path, err)),
+        };
+        let games = serde_json::from_str(&json)
+            .map_err(|err| anyhow!("Failed to parse {:?} {}", self.path, err))?;
+        fs::remove_file(&self.path)
+            .map_err(|err| anyhow!("Failed to remove {:?} {}", self.path, err))?;
+        Ok(games)
+    }
+}
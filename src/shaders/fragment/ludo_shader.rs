@@ -1,7 +1,68 @@
 pub const LUDO_FRAGMENT_SHADER: &str = r#"
-precision mediump float;
+    #ifdef GL_FRAGMENT_PRECISION_HIGH
+    precision highp float;
+    #else
+    precision mediump float;
+    #endif
+    uniform vec3 u_LightColor;
+    uniform vec3 u_LightDirection;
+    uniform vec3 u_AmbientLight;
+    uniform vec3 u_EyePosition;
+    uniform float u_Shininess;
+    // surface detail multiplied into vertex color, a white pixel for plain surfaces.
+    uniform sampler2D u_Texture;
+    uniform sampler2D u_ShadowMap;
+    // 0.0 when shadows are turned off.
+    uniform float u_ShadowEnabled;
+    // size of one texel of shadow map.
+    uniform float u_ShadowTexel;
+
     varying vec4 v_Color;
+    varying vec3 v_Normal;
+    varying vec3 v_Position;
+    varying vec4 v_ShadowPosition;
+    varying vec2 v_TexCoord;
+
+    float unpackDepth(const in vec4 rgbaDepth) {
+        const vec4 bitShift = vec4(1.0, 1.0 / 256.0, 1.0 / (256.0 * 256.0), 1.0 / (256.0 * 256.0 * 256.0));
+        return dot(rgbaDepth, bitShift);
+    }
+
+    // 1.0 when fully lit and less when some caster is b/w light and this fragment.
+    float visibility() {
+        if (u_ShadowEnabled < 0.5) {
+            return 1.0;
+        }
+        vec3 shadowCoord = (v_ShadowPosition.xyz / v_ShadowPosition.w) * 0.5 + 0.5;
+        float lit = 0.0;
+        // 3x3 percentage closer filtering, for softer edges.
+        for (int x = -1; x <= 1; x++) {
+            for (int y = -1; y <= 1; y++) {
+                vec2 offset = vec2(float(x), float(y)) * u_ShadowTexel;
+                float depth = unpackDepth(texture2D(u_ShadowMap, shadowCoord.xy + offset));
+                lit += (shadowCoord.z > depth + 0.002) ? 0.0 : 1.0;
+            }
+        }
+        return 0.4 + 0.6 * lit / 9.0;
+    }
+
     void main() {
-        gl_FragColor = v_Color;
+        vec3 normal = normalize(v_Normal);
+        vec4 color = v_Color * texture2D(u_Texture, v_TexCoord);
+        // if negative, light is behind surface and thus not impactful.
+        float nDotL = max(dot(u_LightDirection, normal), 0.0);
+        vec3 diffuse = u_LightColor * color.rgb * nDotL;
+        vec3 ambient = u_AmbientLight * color.rgb;
+
+        // blinn phong highlight, using half way vector b/w light and eye.
+        vec3 eyeDirection = normalize(u_EyePosition - v_Position);
+        vec3 halfway = normalize(u_LightDirection + eyeDirection);
+        float specular = 0.0;
+        if (nDotL > 0.0) {
+            specular = pow(max(dot(normal, halfway), 0.0), u_Shininess);
+        }
+
+        vec3 lit = diffuse + u_LightColor * specular * 0.4;
+        gl_FragColor = vec4(ambient + lit * visibility(), color.a);
     }
 "#;
@@ -1,5 +1,6 @@
 pub mod cube_shader;
 pub mod fpoint_shader;
 pub mod ludo_shader;
+pub mod shadow_shader;
 pub mod texture_shader;
 pub mod three_triangles;
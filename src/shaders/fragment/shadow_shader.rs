@@ -0,0 +1,15 @@
+pub const SHADOW_FRAGMENT_SHADER: &str = r#"
+    #ifdef GL_FRAGMENT_PRECISION_HIGH
+    precision highp float;
+    #else
+    precision mediump float;
+    #endif
+    // webgl1 can not render into depth texture without extension, so depth is packed in rgba.
+    void main() {
+        const vec4 bitShift = vec4(1.0, 256.0, 256.0 * 256.0, 256.0 * 256.0 * 256.0);
+        const vec4 bitMask = vec4(1.0 / 256.0, 1.0 / 256.0, 1.0 / 256.0, 0.0);
+        vec4 rgbaDepth = fract(gl_FragCoord.z * bitShift);
+        rgbaDepth -= rgbaDepth.gbaa * bitMask;
+        gl_FragColor = rgbaDepth;
+    }
+"#;
@@ -1,12 +1,28 @@
 pub const LUDO_VERTEX_SHADER: &str = r#"
     attribute vec4 a_Position;
-    attribute vec4 b_Position;
     attribute vec4 a_Color;
+    attribute vec3 a_Normal;
+    attribute vec2 a_TexCoord;
     uniform mat4 u_MvpMatrix;
+    uniform mat4 u_ModelMatrix;
+    // inverse transpose of model matrix, so that normals stay perpendicular to surface.
+    uniform mat4 u_NormalMatrix;
+    // mvp matrix as seen from light, used to look up shadow map.
+    uniform mat4 u_LightMvpMatrix;
+    // lets one white mesh be drawn in color of each player.
+    uniform vec4 u_Tint;
 
     varying vec4 v_Color;
+    varying vec3 v_Normal;
+    varying vec3 v_Position;
+    varying vec4 v_ShadowPosition;
+    varying vec2 v_TexCoord;
     void main() {
         gl_Position = u_MvpMatrix * a_Position;
-        v_Color = a_Color;
+        v_Position = vec3(u_ModelMatrix * a_Position);
+        v_Normal = normalize(vec3(u_NormalMatrix * vec4(a_Normal, 0.0)));
+        v_ShadowPosition = u_LightMvpMatrix * a_Position;
+        v_Color = a_Color * u_Tint;
+        v_TexCoord = a_TexCoord;
     }
 "#;
@@ -0,0 +1,8 @@
+pub const SHADOW_VERTEX_SHADER: &str = r#"
+    attribute vec4 a_Position;
+    // mvp matrix as seen from light.
+    uniform mat4 u_MvpMatrix;
+    void main() {
+        gl_Position = u_MvpMatrix * a_Position;
+    }
+"#;
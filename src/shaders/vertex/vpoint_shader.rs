@@ -1,8 +1,8 @@
 pub const VERTEX_SHADER: &str = r##"
     attribute vec4 a_Position;
-    uniform mat4 u_xFormMatrix;
+    uniform mat4 u_MvpMatrix;
     void main() {
-        gl_Position = u_xFormMatrix * a_Position;
+        gl_Position = u_MvpMatrix * a_Position;
         // gl_PointSize = 10.0; - commented as point program is converted to triangle. And it does has affect.
     }
 "##;
@@ -0,0 +1,64 @@
+use std::{cell::RefCell, rc::Rc};
+
+use anyhow::Result;
+use web_sys::{Element, HtmlSelectElement};
+
+use crate::browser::{add_event_listener, append_element, query_param, select};
+use crate::programs::ludo::theme::Theme;
+
+/// Drop down list of themes, which can be changed in the middle of a game.
+/// Built in themes are there from start, theme files are added as they load.
+pub struct ThemePicker {
+    select: HtmlSelectElement,
+    themes: RefCell<Vec<Theme>>,
+    // theme asked for by `?theme=`, it may only turn up once its file has loaded.
+    wanted: Option<String>,
+    on_change: Box<dyn Fn(Theme)>,
+}
+
+impl ThemePicker {
+    pub fn new(on_change: impl Fn(Theme) + 'static) -> Result<Rc<Self>> {
+        let picker = Rc::new(ThemePicker {
+            select: select("theme")?,
+            themes: RefCell::new(Vec::new()),
+            wanted: query_param("theme")?,
+            on_change: Box::new(on_change),
+        });
+        for theme in Theme::built_in() {
+            picker.add(theme)?;
+        }
+
+        {
+            let picker = picker.clone();
+            add_event_listener(
+                &picker.select.clone(),
+                "change",
+                move |_: web_sys::Event| {
+                    // nothing picked is -1, which is past end of list.
+                    let index = picker.select.selected_index() as usize;
+                    let theme = picker.themes.borrow().get(index).cloned();
+                    if let Some(theme) = theme {
+                        (picker.on_change)(theme);
+                    }
+                },
+            )?;
+        }
+        Ok(picker)
+    }
+
+    /// Adds theme at end of list, and picks it if page asked for it.
+    pub fn add(&self, theme: Theme) -> Result<()> {
+        append_element::<Element>(&self.select, "option", &theme.name)?;
+        let wanted = self
+            .wanted
+            .as_ref()
+            .is_some_and(|wanted| wanted.eq_ignore_ascii_case(&theme.name));
+        self.themes.borrow_mut().push(theme.clone());
+        if wanted {
+            self.select
+                .set_selected_index(self.themes.borrow().len() as i32 - 1);
+            (self.on_change)(theme);
+        }
+        Ok(())
+    }
+}